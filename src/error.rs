@@ -0,0 +1,54 @@
+// Errors returned by the program
+
+use solana_program::program_error::ProgramError;      // Error types for the program
+
+// Program-specific errors, surfaced to clients as ProgramError::Custom(code)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardError {
+    VaultNotFunded = 0,           // Vault holds less than the configured minimum
+    ExceedsVaultFractionCap = 1,  // Claim is larger than max_vault_fraction_bps of the vault
+    KycRequired = 2,              // No valid admin KYC attestation precedes the claim
+    PayoutRoundsToZero = 3,       // Claim of zero points, or ClaimAndStake restaking zero points
+    ProgramStillLive = 4,         // CloseConfig before program_end_ts without force
+    ClaimNotYetExecutable = 5,    // ExecuteScheduledClaim before execute_after_ts
+    TooManyScheduledClaims = 6,   // ScheduleClaim beyond max_scheduled_claims
+    ProgramArchived = 7,          // Mutating instruction after the admin archived the program
+    RewardAccountNotEmpty = 8,    // CloseReward with points left (without force) or open scheduled claims
+    BalanceChanged = 9,           // Claim saw a total_points other than the client expected
+    GuardianThresholdNotMet = 10, // RecoverOwnership signed by fewer guardians than the threshold
+    VaultReserveProtected = 11,   // Claim would take the vault below vault_reserve
+    Cooldown = 12,                // Earn within earn_cooldown_secs of the last one
+    MemoRequired = 13,            // No SPL Memo instruction precedes the claim
+    Paused = 14,                  // Earn or claim on a reward account its authority paused
+    CreationCapReached = 15,      // Init beyond max_accounts_per_creator
+    MissingAccount = 16,          // The instruction's account list ends before a required account
+    MintingPaused = 17,           // MintToken while the admin paused minting
+    InvalidOracleSignature = 18,  // ClaimSigned without a matching oracle signature right before it
+    OracleSignatureExpired = 19,  // ClaimSigned after its expiry_ts
+    SignupCapReached = 20,        // Init beyond max_total_users
+    RateLimited = 21,             // Earn of more points than the account's earn bucket holds
+    UnknownReferralCode = 22,     // Init with a referral code the admin never registered
+    MaxStakeExceeded = 23,        // ClaimAndStake beyond max_staked_per_user
+    ProgramPaused = 24,           // Mutating instruction while the dead-man's switch holds the program paused
+    HeartbeatNotExpired = 25,     // TriggerDeadManPause before heartbeat_timeout_secs of admin silence
+    UnknownCohort = 26,           // Init into a cohort at or beyond cohort_count
+    AlreadyClaimedThisEpoch = 27, // Second claim in an epoch under one_claim_per_epoch
+    NotEnoughPoints = 28,         // Claim or pool of more points than the account can spend
+    InvalidVault = 29,            // Vault is not a token account or not held by the vault authority PDA
+    WrongMint = 30,               // Mint or token account of a mint other than the reward mint
+    AlreadyInitialized = 31,      // Init, InitConfig or InitVault on an account that already exists
+    InvalidVoucher = 32,          // EarnWithVoucher without a matching earn authority signature right before it
+    VoucherExpired = 33,          // EarnWithVoucher after the voucher's expiry_ts
+    VoucherAlreadyUsed = 34,      // EarnWithVoucher or ClaimSigned with a nonce below the user's next_nonce
+    AdminPaused = 35,             // Earn, mint, claim or point transfer while the admin has the program paused
+    SupplyCapExceeded = 36,       // MintToken that would push total_minted past max_supply
+    ClaimCooldown = 37,           // Claim within claim_cooldown_secs of the last one
+    InvalidPda = 38,              // Account passed at an address other than the PDA its seeds derive
+    TokenOwnerMismatch = 39,      // Payout token account owned by someone other than the wallet being paid
+}
+
+impl From<RewardError> for ProgramError {
+    fn from(error: RewardError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
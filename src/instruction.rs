@@ -0,0 +1,345 @@
+// Instructions, return data and events exchanged with clients
+
+use alloc::{format, string::String, vec, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};        // For (de)serialization of data structures
+use solana_program::{
+    instruction::{AccountMeta, Instruction},          // Instructions built for clients
+    program_error::ProgramError,                      // Error types for the program
+    pubkey::Pubkey,                                   // Public key type
+    system_program,                                   // Program creating reward accounts
+};
+
+use crate::pda::{
+    find_config_address, find_creator_stats_address, find_mint_authority, find_referral_address, find_reward_address,
+    find_vault_address, find_vault_authority,
+};
+use crate::state::{Config, ConfigParams, REWARD_NOTE_LEN};
+
+// Actions that CanPerform can be asked about
+pub const ACTION_INIT: u8 = 0;
+pub const ACTION_EARN: u8 = 1;
+pub const ACTION_CLAIM: u8 = 2;
+
+// Why CanPerform reports an action as blocked
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockReason {
+    None,                         // The action is allowed
+    AlreadyInitialized,           // Init on an existing reward account
+    NotInitialized,               // The reward account does not exist yet
+    VaultNotFunded,               // Vault holds less than min_vault_balance
+    InsufficientPoints,           // Not a single point left to claim after the fee
+    ProgramArchived,              // The program only serves reads after Archive
+    ProgramPaused,                // The dead-man's switch paused the program
+    AdminPaused,                  // The admin paused earning and claiming with SetProgramPaused
+    AccountPaused,                // The reward account's authority paused it
+    Cooldown,                     // Within earn_cooldown_secs of the last Earn
+    ClaimCooldown,                // Within claim_cooldown_secs of the last claim
+    AlreadyClaimedThisEpoch,      // Already claimed this epoch under one_claim_per_epoch
+    RateLimited,                  // The earn rate-limit bucket is empty
+    SignupCapReached,             // Init beyond max_total_users
+    CreationCapReached,           // Init beyond the rent payer's max_accounts_per_creator
+    KycRequired,                  // No valid admin KYC attestation earlier in the transaction
+    MemoRequired,                 // No SPL Memo instruction earlier in the transaction
+}
+
+// Return data of CanPerform
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct Eligibility {
+    pub allowed: bool,
+    pub reason: BlockReason,
+}
+
+// Return data of GetFullStatus
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct UserStatus {
+    pub total_points: u64,        // Spendable points
+    pub staked_points: u64,       // Points restaked through ClaimAndStake
+    pub pending_points: u64,      // Earns waiting to reach min_recordable_earn
+    pub lifetime_points: u64,     // All points ever credited
+    pub rewards_claimed: u32,     // Number of claims made
+    pub dust_credit: u64,         // Restaked token base units short of a whole point
+    pub claimable_amount: u64,    // Largest payout a single Claim could make right now
+    pub flags: u8,                // STATUS_* bits
+    pub earn_cooldown_remaining: i64,  // Seconds until earn_cooldown_secs lets the next Earn through
+    pub claim_cooldown_remaining: i64, // Seconds until claim_cooldown_secs lets the next claim through
+}
+
+// Bits of UserStatus::flags
+pub const STATUS_ACCOUNT_PAUSED: u8 = 1 << 0;     // The reward account's authority paused it
+pub const STATUS_PROGRAM_PAUSED: u8 = 1 << 1;     // The admin or the dead-man's switch paused the program
+pub const STATUS_PROGRAM_ARCHIVED: u8 = 1 << 2;   // The program only serves reads after Archive
+pub const STATUS_CLAIMED_THIS_EPOCH: u8 = 1 << 3; // No further claim this epoch under one_claim_per_epoch
+
+// Maximum number of reward accounts a single BurnBatch may touch
+pub const MAX_BURN_BATCH: usize = 16;
+
+// Prefix of the message the admin signs to attest a user passed KYC
+pub const KYC_ATTESTATION_PREFIX: &[u8] = b"WAGUS-KYC:";
+
+// Prefix of the message the oracle signs to authorize a ClaimSigned payout
+pub const ORACLE_CLAIM_PREFIX: &[u8] = b"WAGUS-CLAIM:";
+
+// Prefix of the message the earn authority signs to issue an EarnWithVoucher voucher
+pub const EARN_VOUCHER_PREFIX: &[u8] = b"WAGUS-EARN:";
+
+// SPL Memo program (v2), whose instructions satisfy require_memo
+pub const MEMO_PROGRAM_ID: Pubkey = solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// Prefix marking a binary event log line
+pub const BINARY_EVENT_PREFIX: &str = "WGEV:";
+
+// Enum for different reward system instructions. Every instruction starts with the
+// signer, followed by only the accounts it uses; [brackets] mark accounts that only
+// some configs or arguments need, "reward" is the signer's reward PDA:
+// - Init: reward, mint, [system program, left out when no account is created or
+//   upgraded], config, [vault], [creator stats], [referral code], [rent payer]
+// - Earn: reward, [system program, left out when no account is upgraded], config,
+//   earn authority
+// - Claim: reward, user token, vault, mint, token program, config, vault authority,
+//   [instructions sysvar], [mint authority]
+// - ClaimSigned: reward, user token, vault, mint, token program, system program,
+//   config, vault authority, instructions sysvar, nonce registry
+// - ScheduleClaim and OpenStream: reward, user token, vault, system program, config,
+//   scheduled claim or stream, [instructions sysvar]
+// - ExecuteScheduledClaim: user token, vault, mint, token program, config,
+//   scheduled claim, user, vault authority, user's reward PDA
+// - WithdrawStream: reward, user token, vault, mint, token program, config, stream,
+//   vault authority
+// - CancelStream: user token, vault, token program, config, stream, user, vault
+//   authority, user's reward PDA
+// - CancelScheduledClaim: reward, config, scheduled claim
+// - CanPerform: reward, vault, config, [creator stats and rent payer for Init, or
+//   instructions sysvar for Claim]
+// - GetFullStatus: reward, vault, config
+// - MintToken: vault, mint, token program, config, mint authority
+// - Burn: source token, mint, token program, config, [vault authority]
+// - RescueTokens: destination token, source token, token program, config, vault
+//   authority
+// - InitVault: vault, mint, token program, system program, config, vault authority
+// - InitConfig: system program, config, program data, mint
+// - MigrateLegacyLayout: reward, mint, system program, config
+// - MigrateGlobalAccount: reward, legacy [b"reward"] PDA, mint, system program, config
+// - SetNote: reward, system program, config
+// - EarnWithVoucher: reward, system program, config, instructions sysvar, nonce registry
+// - SetGuardians: system program, config, guardian set
+// - SetReferralCode: system program, config, referral code
+// - AdjustLifetime and SetPaused: the user's reward PDA, config
+// - RecoverOwnership: the user's reward PDA, config, guardian set, new owner's reward
+//   PDA, [co-signing guardians]
+// - PoolPoints and MergeAccounts: reward, config, other owner, other owner's reward PDA
+// - ClaimAndStake: reward, config
+// - CloseReward: reward, config, [rent destination]
+// - BurnBatch and ComputeBalanceRoot: config, reward accounts
+// - Every other instruction: config
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum RewardInstruction {
+    Init { idempotency_key: Option<[u8; 32]>, referral_code: Option<[u8; 8]>, cohort: u8 }, // Initialize a new reward account; retries with the same key succeed
+    Earn { points: u32 },         // Earn points, specifying how many
+    Claim { required_points: u32, expected_total_points: Option<u64> }, // Pay out required_points at the exchange rate, optionally pinning the balance
+    MintToken { amount: u64 },                      // Admin: mint into the vault, up to max_supply
+    InitConfig { params: ConfigParams },            // Upgrade authority: create the program config PDA, followed by the program data account and the reward mint
+    ClaimAndStake { required_points: u32, amount: u64 }, // Claim, then restake the payout as points
+    BurnBatch { amounts: Vec<u32> },                // Admin: burn points from the trailing reward accounts
+    ComputeBalanceRoot { first_page: bool, last_page: bool }, // Admin: fold trailing accounts into the balance root
+    AdjustLifetime { user: Pubkey, delta: i64 },    // Admin: correct a user's lifetime points
+    CanPerform { action: u8 },                      // Report via return data whether an action would pass its gates
+    MigrateLegacyLayout,                            // Grow an older reward account layout to the current one
+    GetFullStatus,                                  // Report the user's UserStatus via return data
+    CloseConfig { force: bool },                    // Admin: close the config PDA and reclaim its rent
+    ScheduleClaim { required_points: u32, execute_after_ts: i64 }, // Debit points now, pay out later
+    ExecuteScheduledClaim,                          // Anyone: pay out a scheduled claim that is due
+    CancelScheduledClaim,                           // Refund the points of a pending scheduled claim
+    Archive,                                        // Admin: permanently freeze every mutating instruction
+    CloseReward { force: bool },                    // Close the signer's reward account and reclaim its rent
+    SetGuardians { guardians: Vec<Pubkey>, threshold: u8 }, // Choose the wallets that can recover the signer's points
+    RecoverOwnership { user: Pubkey, new_owner: Pubkey }, // Guardians: move a user's points to new_owner's reward account
+    Burn { amount: u64 },                           // Burn tokens from the token account in the vault slot
+    SetPaused { user: Pubkey, paused: bool },       // Earn authority or admin: halt or resume earning and claiming on a user's account
+    MergeAccounts,                                  // Fold the signer's reward account into a co-signing wallet's and close it
+    SetMintingPaused { paused: bool },              // Admin: stop or resume minting while claims keep paying from the vault
+    SetNote { note: [u8; REWARD_NOTE_LEN] },        // Store opaque bytes on the signer's reward account
+    PoolPoints { amount: u32 },                     // Move some of the signer's points into a shared pool reward account
+    ClaimSigned { amount: u64, nonce: u64, expiry_ts: i64, signature: [u8; 64] }, // Pay out an amount the oracle signed for the signer, once per nonce
+    SetExchangeRate { rate: u64, effective_ts: i64 }, // Admin: queue a new exchange rate behind the timelock
+    CancelPendingRate,                              // Admin: drop a queued exchange rate before it takes effect
+    SetReferralCode { code: [u8; 8], referrer: Pubkey }, // Admin: point a campaign referral code at a referrer
+    Heartbeat,                                      // Admin: prove liveness, lifting a dead-man's switch pause
+    TriggerDeadManPause,                            // Anyone: pause the program once the admin's heartbeat lapsed
+    OpenStream { required_points: u32, total_amount: u64, duration: u32 }, // Debit points now, stream the payout per second
+    WithdrawStream,                                 // Pay out what a stream has accrued so far
+    CancelStream,                                   // Settle a stream's accrued payout and refund the rest as points
+    RescueTokens { amount: u64 },                   // Admin: move stray tokens out of a vault authority token account
+    UpdateConfig { params: ConfigParams },          // Admin: replace the config parameters, keeping the exchange rate
+    InitVault,                                      // Admin: create the mint's vault token account, owned by the vault authority PDA
+    EarnWithVoucher { points: u32, nonce: u64, expiry_ts: i64, signature: [u8; 64] }, // Earn points the earn authority signed a voucher for
+    SetProgramPaused { paused: bool },              // Admin: halt or resume earning, minting and payouts during an incident
+    ProposeAdmin { new_admin: Pubkey },             // Admin: nominate a successor (default key withdraws the nomination)
+    AcceptAdmin,                                    // Pending admin: take over the config from the current admin
+    MigrateGlobalAccount,                           // Admin: move the legacy [b"reward"] account into the admin's reward PDA and close it
+}
+
+// Init for `user` under `config`: signer, reward PDA, reward mint, system program and
+// config, then the mint's vault under require_funded_vault, the rent payer's creator
+// stats PDA under max_accounts_per_creator, the referral code PDA when a code is
+// given and a co-signing `payer` sponsoring the rent
+pub fn init(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    config: &Config,
+    payer: Option<&Pubkey>,
+    idempotency_key: Option<[u8; 32]>,
+    referral_code: Option<[u8; 8]>,
+    cohort: u8,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = short_accounts(program_id, user, &config.mint);
+    accounts.push(AccountMeta::new(find_config_address(program_id).0, false));
+    if config.params.require_funded_vault {
+        accounts.push(AccountMeta::new_readonly(find_vault_address(program_id, &config.mint).0, false));
+    }
+    if config.params.max_accounts_per_creator != 0 {
+        let creator = payer.unwrap_or(user);
+        accounts.push(AccountMeta::new(find_creator_stats_address(program_id, creator).0, false));
+    }
+    if let Some(code) = referral_code {
+        let referral = find_referral_address(program_id, &code).0;
+        accounts.push(AccountMeta::new_readonly(referral, false));
+    }
+    if let Some(payer) = payer {
+        accounts.push(AccountMeta::new(*payer, true));
+    }
+    let data = borsh::to_vec(&RewardInstruction::Init { idempotency_key, referral_code, cohort })?;
+    Ok(Instruction { program_id: *program_id, accounts, data })
+}
+
+// Earn of `points` for `user`, co-signed by the config's earn_authority
+pub fn earn(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    authority: &Pubkey,
+    points: u32,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(find_reward_address(program_id, user).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.push(AccountMeta::new(find_config_address(program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(*authority, true));
+    let data = borsh::to_vec(&RewardInstruction::Earn { points })?;
+    Ok(Instruction { program_id: *program_id, accounts, data })
+}
+
+// Claim paying `user_token` from `vault` through the vault authority PDA under
+// `config`, followed by the instructions sysvar under require_memo or require_kyc
+// and the mint authority PDA under auto_replenish
+pub fn claim(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    vault: &Pubkey,
+    config: &Config,
+    required_points: u32,
+    expected_total_points: Option<u64>,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = base_accounts(program_id, user, user_token, vault, &config.mint);
+    accounts.push(AccountMeta::new(find_config_address(program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(find_vault_authority(program_id).0, false));
+    if config.params.require_kyc || config.params.require_memo {
+        accounts.push(AccountMeta::new_readonly(solana_instructions_sysvar::id(), false));
+    }
+    if config.params.auto_replenish {
+        accounts.push(AccountMeta::new_readonly(find_mint_authority(program_id).0, false));
+    }
+    let data = borsh::to_vec(&RewardInstruction::Claim { required_points, expected_total_points })?;
+    Ok(Instruction { program_id: *program_id, accounts, data })
+}
+
+// InitVault creating the vault token account of `mint`, signed by the admin
+pub fn init_vault(program_id: &Pubkey, admin: &Pubkey, mint: &Pubkey) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(find_vault_address(program_id, mint).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(find_vault_authority(program_id).0, false),
+    ];
+    let data = borsh::to_vec(&RewardInstruction::InitVault)?;
+    Ok(Instruction { program_id: *program_id, accounts, data })
+}
+
+// Signer, reward PDA, user token, vault, mint and token program
+fn base_accounts(program_id: &Pubkey, user: &Pubkey, user_token: &Pubkey, vault: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(find_reward_address(program_id, user).0, false),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+// Init skips the token accounts
+fn short_accounts(program_id: &Pubkey, user: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(find_reward_address(program_id, user).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]
+}
+
+// Message the admin signs to attest that `user` passed KYC
+pub fn kyc_attestation_message(user: &Pubkey) -> Vec<u8> {
+    [KYC_ATTESTATION_PREFIX, user.as_ref()].concat()
+}
+
+// Message the oracle signs to authorize paying `amount` to `user` under `nonce` until `expiry_ts`
+pub fn oracle_claim_message(user: &Pubkey, amount: u64, nonce: u64, expiry_ts: i64) -> Vec<u8> {
+    [ORACLE_CLAIM_PREFIX, user.as_ref(), &amount.to_le_bytes(), &nonce.to_le_bytes(), &expiry_ts.to_le_bytes()].concat()
+}
+
+// Message the earn authority signs to let `user` redeem voucher `nonce` for `points` until `expiry_ts`
+pub fn earn_voucher_message(user: &Pubkey, points: u32, nonce: u64, expiry_ts: i64) -> Vec<u8> {
+    [EARN_VOUCHER_PREFIX, user.as_ref(), &points.to_le_bytes(), &nonce.to_le_bytes(), &expiry_ts.to_le_bytes()].concat()
+}
+
+// Events logged by the program, each exactly once. By default an event is
+// logged as program data holding its Borsh encoding (variant index byte, then
+// the fields in little-endian order). With `binary_events` enabled it is logged
+// instead as a single line: BINARY_EVENT_PREFIX followed by the base64 of that
+// encoding.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub enum RewardEvent {
+    Earned { user: Pubkey, points: u64, new_total: u64 },
+    Claimed { user: Pubkey, required_points: u64, amount: u64, decimals: u8 },
+    Restaked { user: Pubkey, required_points: u64, staked_points: u64 },
+    Minted { amount: u64 },
+}
+
+// Format the log line emitted for an event when `binary_events` is enabled
+pub fn binary_event_line(event: &RewardEvent) -> Result<String, ProgramError> {
+    let data = borsh::to_vec(event)?;
+    Ok(format!("{}{}", BINARY_EVENT_PREFIX, encode_base64(&data)))
+}
+
+// Standard base64 (RFC 4648) with padding
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(word >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,77 @@
+// Program-derived addresses, so clients and CPI callers derive the same seeds as the processor
+
+use solana_program::pubkey::Pubkey;                   // Public key type
+
+// Seed prefixes of the program's PDAs
+pub const REWARD_SEED: &[u8] = b"reward";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+pub const NONCES_SEED: &[u8] = b"nonces";
+pub const SCHEDULED_CLAIM_SEED: &[u8] = b"scheduled_claim";
+pub const STREAM_SEED: &[u8] = b"stream";
+pub const GUARDIANS_SEED: &[u8] = b"guardians";
+pub const CREATOR_STATS_SEED: &[u8] = b"creator_stats";
+pub const REFERRAL_SEED: &[u8] = b"referral";
+
+// Reward account of `user`: [b"reward", user]
+pub fn find_reward_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_SEED, user.as_ref()], program_id)
+}
+
+// Single reward account shared by every user before the PDA took the user's key: [b"reward"].
+// Only MigrateGlobalAccount still reads it
+pub fn find_legacy_reward_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_SEED], program_id)
+}
+
+// Program config: [b"config"]
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+// Vault token account InitVault creates for `mint`: [b"vault", mint]
+pub fn find_vault_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, mint.as_ref()], program_id)
+}
+
+// Owner of the vault, signing claim payouts: [b"vault_authority"]
+pub fn find_vault_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED], program_id)
+}
+
+// Mint authority used to replenish the vault: [b"mint_authority"]
+pub fn find_mint_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], program_id)
+}
+
+// Voucher and oracle claim nonces `user` has consumed: [b"nonces", user]
+pub fn find_nonce_registry_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NONCES_SEED, user.as_ref()], program_id)
+}
+
+// Scheduled claim number `id` of `user`: [b"scheduled_claim", user, id as u32 LE]
+pub fn find_scheduled_claim_address(program_id: &Pubkey, user: &Pubkey, id: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEDULED_CLAIM_SEED, user.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+// Stream number `id` of `user`: [b"stream", user, id as u32 LE]
+pub fn find_stream_address(program_id: &Pubkey, user: &Pubkey, id: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STREAM_SEED, user.as_ref(), &id.to_le_bytes()], program_id)
+}
+
+// Recovery guardians of `user`: [b"guardians", user]
+pub fn find_guardian_set_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GUARDIANS_SEED, user.as_ref()], program_id)
+}
+
+// Reward accounts `creator` has paid for, under max_accounts_per_creator: [b"creator_stats", creator]
+pub fn find_creator_stats_address(program_id: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CREATOR_STATS_SEED, creator.as_ref()], program_id)
+}
+
+// Referrer registered for `code`: [b"referral", code]
+pub fn find_referral_address(program_id: &Pubkey, code: &[u8; 8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REFERRAL_SEED, code], program_id)
+}
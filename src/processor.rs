@@ -0,0 +1,2738 @@
+// Instruction processing
+
+use alloc::{format, vec::Vec};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},  // For accessing account info
+    entrypoint::ProgramResult,                        // Return type for program functions
+    msg,                                              // Macro for logging messages
+    log::sol_log_data,                                // Log binary program data for indexers
+    program::{invoke, invoke_signed, set_return_data}, // Invoke another instruction (optionally as a PDA)
+    program_error::ProgramError,                      // Error types for the program
+    ed25519_program,                                  // Native program verifying ed25519 signatures
+    bpf_loader_upgradeable,                           // Loader holding the program's upgrade authority
+    program_pack::Pack,                               // Unpacking SPL Token account state
+    pubkey::Pubkey,                                   // Public key type
+    clock::Clock,                                     // Current unix timestamp
+    rent::Rent,                                       // Rent system used for accounts
+    system_instruction,                               // System program instructions (account creation)
+    system_program,                                   // Owner of closed accounts
+    sysvar::Sysvar,                                   // Access to system variables (like rent)
+};
+use spl_token::instruction::{burn, initialize_account3, mint_to, transfer}; // Burn, mint, transfer and account setup instructions for the SPL Token program
+use spl_token::state::{Account as TokenAccount, Mint}; // SPL Token account and mint state
+use borsh::BorshDeserialize;                          // For (de)serialization of data structures
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked}; // Inspect sibling instructions
+
+use crate::error::RewardError;
+use crate::instruction::*;
+use crate::pda::*;
+use crate::state::*;
+
+// Log a human-readable line unless the deployment only logs binary events
+macro_rules! prose {
+    ($params:expr, $($arg:tt)+) => {
+        if !$params.binary_events {
+            msg!($($arg)+);
+        }
+    };
+}
+
+// Main function to process the instructions
+pub fn process_instruction(
+    program_id: &Pubkey,               // The public key of the program being executed
+    accounts: &[AccountInfo],          // List of accounts involved in the instruction
+    instruction_data: &[u8],           // Data passed with the instruction (e.g., action type)
+) -> ProgramResult {
+    // Log the entry point of the reward system program, unless the deployment only logs its events
+    let prose_logs = !binary_events_enabled(program_id, accounts);
+    if prose_logs {
+        msg!("Reward System Program Entry");
+        msg!("Instruction data: {:?}", instruction_data);
+    }
+
+    // Every instruction starts with the signer, followed by the accounts of its own layout
+    let accounts_iter = &mut accounts.iter();
+
+    // Ensure that the signer has signed the transaction
+    let signer = next_named_account(accounts_iter, "signer")?;
+    if !signer.is_signer {
+        if prose_logs {
+            msg!("Missing required signature for signer: {}", signer.key);
+        }
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize instruction data into the RewardInstruction enum
+    let instruction = RewardInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    // Match on the instruction to perform the correct action. Each wallet has its own
+    // reward account, which only the instructions working on it take after the signer
+    match instruction {
+        RewardInstruction::Init { idempotency_key, referral_code, cohort } => {
+            let (reward_account_info, reward_bump) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let args = InitArgs { idempotency_key, referral_code, cohort };
+            process_init(program_id, signer, reward_account_info, reward_bump, args, accounts_iter)?;
+        }
+
+        RewardInstruction::Earn { points } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            process_earn(program_id, signer, reward_account_info, points, accounts_iter)?;
+        }
+
+        RewardInstruction::Claim { required_points, expected_total_points } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let TokenAccounts { user_token_account, vault_token_account, mint_account, token_program } = next_token_accounts(accounts_iter)?;
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            // The vault is owned by the vault authority PDA, which signs the payout
+            let vault_authority_info = next_account_info(accounts_iter)?;
+            // A claim spending no points would pay nothing while still counting as one
+            if required_points == 0 {
+                prose!(config.params, "Claim must spend at least one point");
+                return Err(RewardError::PayoutRoundsToZero.into());
+            }
+            let amount = claim_amount(&config, required_points)?;
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+            // Clients wanting strict consistency pin the balance they validated the claim against
+            if let Some(expected) = expected_total_points {
+                if reward_account.total_points != expected {
+                    prose!(config.params, "Expected {} points but the account holds {}", expected, reward_account.total_points);
+                    return Err(RewardError::BalanceChanged.into());
+                }
+            }
+            validate_payout_accounts(&config, signer.key, user_token_account, vault_token_account)?;
+            let decimals = mint_decimals(&config, mint_account)?;
+
+            debit_claim(&mut reward_account, &mut config, required_points)?;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+
+            // Regulated deployments check the rest of the transaction for a KYC
+            // attestation and/or memo, with the instructions sysvar following the config
+            if config.params.require_kyc || config.params.require_memo {
+                verify_claim_siblings(&config, signer.key, next_account_info(accounts_iter)?)?;
+            }
+
+            // Top up a low vault before paying out, with the mint authority PDA next
+            if config.params.auto_replenish {
+                let mint_authority_info = next_account_info(accounts_iter)?;
+                replenish_vault(
+                    program_id,
+                    &mut config,
+                    vault_token_account,
+                    mint_account,
+                    mint_authority_info,
+                    token_program,
+                )?;
+            }
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+            pay_from_vault(
+                program_id,
+                &config,
+                token_program,
+                vault_token_account,
+                user_token_account,
+                vault_authority_info,
+                amount,
+            )?;
+
+            emit_event(&config, &RewardEvent::Claimed {
+                user: *signer.key,
+                required_points: required_points.into(),
+                amount,
+                decimals,
+            })?;
+            prose!(config.params, "Transferred {} WAGUS tokens as reward!", amount);
+        }
+
+        RewardInstruction::MintToken { amount } => {
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            let token_program = next_token_program(accounts_iter)?;
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            if config.minting_paused {
+                prose!(config.params, "Minting is paused");
+                return Err(RewardError::MintingPaused.into());
+            }
+            let mint_authority_info = next_account_info(accounts_iter)?;
+
+            // Only the reward mint is minted, and only into its vault PDA
+            if *mint_account.key != config.mint {
+                prose!(config.params, "Mint does not match the reward mint {}", config.mint);
+                return Err(RewardError::WrongMint.into());
+            }
+            if *vault_token_account.key != find_vault_address(program_id, &config.mint).0 {
+                prose!(config.params, "Tokens are only minted into the vault PDA of the reward mint");
+                return Err(RewardError::InvalidVault.into());
+            }
+            if !within_supply_cap(&config, amount)? {
+                prose!(config.params, "Minting {} would exceed the supply cap of {}", amount, config.params.max_supply);
+                return Err(RewardError::SupplyCapExceeded.into());
+            }
+
+            mint_to_vault(program_id, &mut config, vault_token_account, mint_account, mint_authority_info, token_program, amount)?;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Minted {} tokens into the vault, {} in total", amount, config.total_minted);
+        }
+
+        RewardInstruction::InitConfig { params } => {
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config_info = next_account_info(accounts_iter)?;
+            let (config_pda, config_bump) = find_config_address(program_id);
+            if config_info.key != &config_pda {
+                prose!(params, "Invalid config account PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+            if !config_info.data_is_empty() {
+                prose!(params, "Config already initialized");
+                return Err(RewardError::AlreadyInitialized.into());
+            }
+            // Only the upgrade authority may create the config, so nobody else can take over after CloseConfig
+            require_upgrade_authority(program_id, &params, signer, next_named_account(accounts_iter, "program data")?)?;
+            // The mint every reward account, claim and vault is bound to
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            if *mint_account.owner != spl_token::id() {
+                prose!(params, "Mint is not a token mint");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            validate_params(&params)?;
+
+            // Create the config PDA, paid for by the signer who becomes its admin
+            create_pda_account(
+                signer,
+                config_info,
+                CONFIG_SIZE,
+                program_id,
+                system_program,
+                &[CONFIG_SEED, &[config_bump]],
+            )?;
+
+            let config = Config {
+                admin: *signer.key,
+                mint: *mint_account.key,
+                params,
+                total_points_destroyed: 0,
+                balance_root: BalanceRoot::default(),
+                archived: false,
+                minting_paused: false,
+                total_users: 0,
+                pending_exchange_rate: 0,
+                pending_rate_effective_ts: 0,
+                last_heartbeat_ts: Clock::get()?.unix_timestamp,
+                dead_man_paused: false,
+                program_paused: false,
+                pending_admin: Pubkey::default(),
+                total_minted: 0,
+                bump: config_bump,
+                vault_authority_bump: find_vault_authority(program_id).1,
+                mint_authority_bump: find_mint_authority(program_id).1,
+            };
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Config initialized with exchange rate {}", config.params.exchange_rate);
+        }
+
+        RewardInstruction::ClaimAndStake { required_points, amount } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+
+            // The restaked payout can never be worth more than the points spent on it
+            let rate = exchange_rate(&config)?;
+            let max_amount = (required_points as u64)
+                .checked_mul(rate)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if amount > max_amount {
+                prose!(config.params, "Claim amount exceeds the value of the spent points");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Convert the token payout back into points instead of transferring it
+            let mut staked = amount
+                .checked_div(rate)
+                .ok_or(ProgramError::InvalidAccountData)?;
+            if config.params.accumulate_dust {
+                // Keep the sub-point remainder and release it once it adds up to whole points
+                let dust = reward_account.dust_credit.checked_add(amount % rate)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+                staked += dust / rate;
+                reward_account.dust_credit = dust % rate;
+            } else if staked == 0 {
+                prose!(config.params, "Restaking {} tokens rounds down to zero points", amount);
+                return Err(RewardError::PayoutRoundsToZero.into());
+            }
+            debit_claim(&mut reward_account, &mut config, required_points)?;
+            reward_account.staked_points = reward_account.staked_points.checked_add(staked)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let max_staked = config.params.max_staked_per_user;
+            if max_staked != 0 && reward_account.staked_points > max_staked as u64 {
+                prose!(config.params, "Staking {} points would exceed the {} point cap", staked, max_staked);
+                return Err(RewardError::MaxStakeExceeded.into());
+            }
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+            emit_event(&config, &RewardEvent::Restaked {
+                user: *signer.key,
+                required_points: required_points.into(),
+                staked_points: staked,
+            })?;
+            prose!(config.params, "Restaked {} WAGUS tokens as {} staked points!", amount, staked);
+        }
+
+        RewardInstruction::BurnBatch { amounts } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // Remaining accounts are the reward accounts to burn from
+            let targets = accounts_iter.as_slice();
+            if targets.is_empty() || targets.len() > MAX_BURN_BATCH {
+                prose!(config.params, "Burn batch must contain between 1 and {} accounts", MAX_BURN_BATCH);
+                return Err(ProgramError::InvalidArgument);
+            }
+            // A single amount applies to every account, otherwise one amount per account
+            if amounts.len() != 1 && amounts.len() != targets.len() {
+                prose!(config.params, "Expected 1 or {} burn amounts, got {}", targets.len(), amounts.len());
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let mut destroyed: u64 = 0;
+            for (index, target) in targets.iter().enumerate() {
+                if target.owner != program_id || target.data_len() != REWARD_ACCOUNT_SIZE {
+                    prose!(config.params, "Account {} is not a reward account", target.key);
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let mut target_account = RewardAccount::try_from_account_data(&target.data.borrow())?;
+
+                // Clamp at zero rather than failing the whole batch
+                let amount = amounts[if amounts.len() == 1 { 0 } else { index }];
+                let burned = (amount as u64).min(target_account.total_points);
+                target_account.total_points -= burned;
+                target_account.write_account_data(&mut target.data.borrow_mut())?;
+                destroyed += burned;
+            }
+
+            config.total_points_destroyed = config.total_points_destroyed.checked_add(destroyed)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Burned {} points across {} accounts", destroyed, targets.len());
+        }
+
+        RewardInstruction::ComputeBalanceRoot { first_page, last_page } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // The first page starts a new snapshot; later pages extend the open one
+            if first_page {
+                config.balance_root = BalanceRoot::default();
+            } else if config.balance_root.finalized {
+                prose!(config.params, "Balance root already finalized, start a new snapshot with first_page");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let page = accounts_iter.as_slice();
+            for target in page {
+                if target.owner != program_id || target.data_len() != REWARD_ACCOUNT_SIZE {
+                    prose!(config.params, "Account {} is not a reward account", target.key);
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let target_account = RewardAccount::try_from_account_data(&target.data.borrow())?;
+                // Keyed by the owning wallet, so a proof names who holds the balance rather than where it is stored
+                config.balance_root.fold(balance_leaf(&target_account.owner, target_account.total_points))?;
+            }
+
+            if last_page {
+                config.balance_root.root = config.balance_root.compute_root();
+                config.balance_root.finalized = true;
+                prose!(config.params, "Balance root finalized over {} accounts", config.balance_root.leaf_count);
+            } else {
+                prose!(config.params, "Folded {} accounts into the balance root", page.len());
+            }
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+        }
+
+        RewardInstruction::AdjustLifetime { user, delta } => {
+            // Admin corrections target the given user's reward account
+            let (reward_account_info, _) = next_reward_account(program_id, &user, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // Saturate at the bounds of the counter instead of failing the correction
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            let previous = reward_account.lifetime_points;
+            let adjusted = previous as i128 + delta as i128;
+            reward_account.lifetime_points = adjusted.clamp(0, u64::MAX as i128) as u64;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+
+            prose!(config.params, 
+                "Adjusted lifetime points of {} by {}: {} -> {}",
+                user,
+                delta,
+                previous,
+                reward_account.lifetime_points
+            );
+        }
+
+        RewardInstruction::CanPerform { action } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            let clock = Clock::get()?;
+
+            // Run the same gates as the action itself without writing anything. The accounts those
+            // gates read follow the config: the creator stats PDA and optional rent payer for Init
+            // under a creation cap, and the instructions sysvar for Claim under require_kyc or require_memo
+            let reason = match action {
+                ACTION_INIT | ACTION_EARN | ACTION_CLAIM if config.archived => BlockReason::ProgramArchived,
+                ACTION_INIT | ACTION_EARN | ACTION_CLAIM if config.dead_man_paused => BlockReason::ProgramPaused,
+                ACTION_EARN | ACTION_CLAIM if config.program_paused => BlockReason::AdminPaused,
+                ACTION_INIT => {
+                    let max_users = config.params.max_total_users;
+                    if !reward_account_info.data_is_empty() {
+                        BlockReason::AlreadyInitialized
+                    } else if !vault_funded(program_id, &config, vault_token_account)? {
+                        BlockReason::VaultNotFunded
+                    } else if max_users != 0 && config.total_users >= max_users {
+                        BlockReason::SignupCapReached
+                    } else if config.params.max_accounts_per_creator != 0 {
+                        // The cap counts the rent payer's Inits, a sponsoring relayer following the stats PDA
+                        let creator_stats_info = next_named_account(accounts_iter, "creator stats")?;
+                        let payer = accounts_iter.next().unwrap_or(signer);
+                        match creation_cap_reached(program_id, &config, payer.key, creator_stats_info)? {
+                            true => BlockReason::CreationCapReached,
+                            false => BlockReason::None,
+                        }
+                    } else {
+                        BlockReason::None
+                    }
+                }
+                ACTION_EARN | ACTION_CLAIM if reward_account_info.data_is_empty() => BlockReason::NotInitialized,
+                ACTION_EARN => {
+                    let reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+                    if reward_account.paused {
+                        BlockReason::AccountPaused
+                    } else if cooldown_remaining(reward_account.last_earn_ts, config.params.earn_cooldown_secs, clock.unix_timestamp) != 0 {
+                        BlockReason::Cooldown
+                    } else if config.params.earn_bucket_capacity != 0
+                        && earn_tokens_available(&config, &reward_account, clock.unix_timestamp)? == 0
+                    {
+                        BlockReason::RateLimited
+                    } else {
+                        BlockReason::None
+                    }
+                }
+                ACTION_CLAIM => {
+                    let reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+                    if reward_account.paused {
+                        BlockReason::AccountPaused
+                    } else if cooldown_remaining(reward_account.last_claim_ts, config.params.claim_cooldown_secs, clock.unix_timestamp) != 0 {
+                        BlockReason::ClaimCooldown
+                    } else if config.params.one_claim_per_epoch && clock.epoch < reward_account.next_claim_epoch {
+                        BlockReason::AlreadyClaimedThisEpoch
+                    } else if reward_account.total_points <= config.params.claim_point_fee as u64 {
+                        BlockReason::InsufficientPoints
+                    } else if config.params.require_kyc || config.params.require_memo {
+                        match verify_claim_siblings(&config, signer.key, next_named_account(accounts_iter, "instructions sysvar")?) {
+                            Ok(()) => BlockReason::None,
+                            Err(error) if error == RewardError::KycRequired.into() => BlockReason::KycRequired,
+                            Err(error) if error == RewardError::MemoRequired.into() => BlockReason::MemoRequired,
+                            Err(error) => return Err(error),
+                        }
+                    } else {
+                        BlockReason::None
+                    }
+                }
+                _ => {
+                    prose!(config.params, "Unknown action {}", action);
+                    return Err(ProgramError::InvalidArgument);
+                }
+            };
+
+            let eligibility = Eligibility { allowed: reason == BlockReason::None, reason };
+            set_return_data(&borsh::to_vec(&eligibility)?);
+            prose!(config.params, "Action {} allowed: {} ({:?})", action, eligibility.allowed, reason);
+        }
+
+        RewardInstruction::MigrateLegacyLayout => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_named_account(accounts_iter, "config")?)?;
+            require_active(&config)?;
+            if migrate_reward_account(program_id, &config, reward_account_info, signer, Some(mint_account), Some(system_program))? {
+                prose!(config.params, "Migrated reward account to layout version {}", REWARD_ACCOUNT_VERSION);
+            } else {
+                prose!(config.params, "Reward account already migrated");
+            }
+        }
+
+        RewardInstruction::MigrateGlobalAccount => {
+            let (reward_account_info, reward_bump) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let legacy_info = next_named_account(accounts_iter, "legacy reward")?;
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config_info = next_named_account(accounts_iter, "config")?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            // The shared account never recorded whose points it holds, so only the admin may take them over
+            require_admin(&config, signer)?;
+
+            if *legacy_info.key != find_legacy_reward_address(program_id).0 || legacy_info.owner != program_id {
+                prose!(config.params, "Invalid legacy reward account PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+            if !reward_account_info.data_is_empty() {
+                prose!(config.params, "Account already initialized");
+                return Err(RewardError::AlreadyInitialized.into());
+            }
+            let reward_account = RewardAccount {
+                bump: reward_bump,
+                ..read_legacy_reward_account(&config, &legacy_info.data.borrow(), signer.key, Some(mint_account))?
+            };
+
+            create_pda_account(
+                signer,
+                reward_account_info,
+                REWARD_ACCOUNT_SIZE,
+                program_id,
+                system_program,
+                &[REWARD_SEED, signer.key.as_ref(), &[reward_bump]],
+            )?;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            config.total_users = config.total_users.saturating_add(1);
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+            // The shared account is closed so its points cannot be moved twice
+            let lamports = close_account(legacy_info, signer)?;
+            prose!(config.params, "Moved {} points from the legacy reward account, {} lamports returned", reward_account.total_points, lamports);
+        }
+
+        RewardInstruction::GetFullStatus => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            let reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+
+            // Every point beyond the claim fee, bounded by the vault fraction cap and reserve
+            let claimable_amount = reward_account.total_points.saturating_sub(config.params.claim_point_fee as u64)
+                .saturating_mul(exchange_rate(&config)?)
+                .min(vault_claim_cap(&config, vault_token_account)?)
+                .min(vault_reserve_headroom(&config, vault_token_account)?);
+            let clock = Clock::get()?;
+            let mut flags = 0;
+            if reward_account.paused {
+                flags |= STATUS_ACCOUNT_PAUSED;
+            }
+            if config.program_paused || config.dead_man_paused {
+                flags |= STATUS_PROGRAM_PAUSED;
+            }
+            if config.archived {
+                flags |= STATUS_PROGRAM_ARCHIVED;
+            }
+            if config.params.one_claim_per_epoch && clock.epoch < reward_account.next_claim_epoch {
+                flags |= STATUS_CLAIMED_THIS_EPOCH;
+            }
+
+            let status = UserStatus {
+                total_points: reward_account.total_points,
+                staked_points: reward_account.staked_points,
+                pending_points: reward_account.pending_points,
+                lifetime_points: reward_account.lifetime_points,
+                rewards_claimed: reward_account.rewards_claimed,
+                dust_credit: reward_account.dust_credit,
+                claimable_amount,
+                flags,
+                earn_cooldown_remaining: cooldown_remaining(reward_account.last_earn_ts, config.params.earn_cooldown_secs, clock.unix_timestamp),
+                claim_cooldown_remaining: cooldown_remaining(reward_account.last_claim_ts, config.params.claim_cooldown_secs, clock.unix_timestamp),
+            };
+            set_return_data(&borsh::to_vec(&status)?);
+        }
+
+        RewardInstruction::CloseConfig { force } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            // Closing the config would let a fresh InitConfig lift the archive
+            require_active(&config)?;
+
+            // Closing a live deployment would brick every instruction that loads the config
+            let end_ts = config.params.program_end_ts;
+            let ended = end_ts != 0 && Clock::get()?.unix_timestamp >= end_ts;
+            if !ended && !force {
+                prose!(config.params, "Program is still live, close it after {} or force it", end_ts);
+                return Err(RewardError::ProgramStillLive.into());
+            }
+
+            // Return the rent to the admin
+            let lamports = close_account(config_info, signer)?;
+            prose!(config.params, "Config closed, {} lamports returned to the admin", lamports);
+        }
+
+        RewardInstruction::ScheduleClaim { required_points, execute_after_ts } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let user_token_account = next_named_account(accounts_iter, "user token")?;
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let amount = claim_amount(&config, required_points)?;
+            let scheduled_info = next_account_info(accounts_iter)?;
+            if config.params.require_kyc || config.params.require_memo {
+                verify_claim_siblings(&config, signer.key, next_account_info(accounts_iter)?)?;
+            }
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+
+            let id = reward_account.next_schedule_id;
+            let (scheduled_pda, scheduled_bump) = find_scheduled_claim_address(program_id, signer.key, id);
+            if scheduled_info.key != &scheduled_pda {
+                prose!(config.params, "Invalid scheduled claim PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+
+            validate_payout_accounts(&config, signer.key, user_token_account, vault_token_account)?;
+
+            // Bound how many scheduled claim accounts a single user can keep open
+            let max_scheduled = config.params.max_scheduled_claims;
+            if max_scheduled != 0 && reward_account.scheduled_claims >= max_scheduled {
+                prose!(config.params, "User already has {} scheduled claims", reward_account.scheduled_claims);
+                return Err(RewardError::TooManyScheduledClaims.into());
+            }
+
+            debit_claim(&mut reward_account, &mut config, required_points)?;
+            reward_account.next_schedule_id = id.checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.scheduled_claims = reward_account.scheduled_claims.checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+            // The signer pays rent for the scheduled claim until it is executed or cancelled
+            create_pda_account(
+                signer,
+                scheduled_info,
+                SCHEDULED_CLAIM_SIZE,
+                program_id,
+                system_program,
+                &[SCHEDULED_CLAIM_SEED, signer.key.as_ref(), &id.to_le_bytes(), &[scheduled_bump]],
+            )?;
+
+            let scheduled = ScheduledClaim {
+                user: *signer.key,
+                id,
+                destination: *user_token_account.key,
+                required_points: required_points.into(),
+                amount,
+                execute_after_ts,
+            };
+            scheduled.write_account_data(&mut scheduled_info.data.borrow_mut())?;
+            prose!(config.params, "Scheduled claim {} of {} WAGUS tokens after {}", id, amount, execute_after_ts);
+        }
+
+        RewardInstruction::ExecuteScheduledClaim => {
+            let TokenAccounts { user_token_account, vault_token_account, mint_account, token_program } = next_token_accounts(accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let scheduled_info = next_account_info(accounts_iter)?;
+            let user_info = next_account_info(accounts_iter)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+            let user_reward_info = next_account_info(accounts_iter)?;
+
+            let scheduled = load_scheduled_claim(program_id, &config, scheduled_info)?;
+            if user_info.key != &scheduled.user
+                || user_token_account.key != &scheduled.destination
+                || reward_address_bump(program_id, &scheduled.user, user_reward_info).is_none()
+            {
+                prose!(config.params, "Accounts do not match the scheduled claim");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if Clock::get()?.unix_timestamp < scheduled.execute_after_ts {
+                prose!(config.params, "Scheduled claim executes after {}", scheduled.execute_after_ts);
+                return Err(RewardError::ClaimNotYetExecutable.into());
+            }
+            let mut user_reward = RewardAccount::try_from_account_data(&user_reward_info.data.borrow())?;
+            validate_payout_accounts(&config, &scheduled.user, user_token_account, vault_token_account)?;
+            let decimals = mint_decimals(&config, mint_account)?;
+
+            // The executor is not the vault owner, so the vault authority PDA signs the payout
+            pay_from_vault(
+                program_id,
+                &config,
+                token_program,
+                vault_token_account,
+                user_token_account,
+                vault_authority_info,
+                scheduled.amount,
+            )?;
+
+            user_reward.scheduled_claims = user_reward.scheduled_claims.saturating_sub(1);
+            user_reward.write_account_data(&mut user_reward_info.data.borrow_mut())?;
+
+            close_account(scheduled_info, user_info)?;
+            emit_event(&config, &RewardEvent::Claimed {
+                user: scheduled.user,
+                required_points: scheduled.required_points,
+                amount: scheduled.amount,
+                decimals,
+            })?;
+            prose!(config.params, "Transferred {} WAGUS tokens for scheduled claim {}", scheduled.amount, scheduled.id);
+        }
+
+        RewardInstruction::CancelScheduledClaim => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            let scheduled_info = next_account_info(accounts_iter)?;
+            let scheduled = load_scheduled_claim(program_id, &config, scheduled_info)?;
+            if scheduled.user != *signer.key {
+                prose!(config.params, "Only {} can cancel this scheduled claim", scheduled.user);
+                return Err(ProgramError::IncorrectAuthority);
+            }
+
+            // The spent points come back; the claim fee stays burned
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            reward_account.total_points = reward_account.total_points.checked_add(scheduled.required_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.rewards_claimed = reward_account.rewards_claimed.saturating_sub(1);
+            reward_account.scheduled_claims = reward_account.scheduled_claims.saturating_sub(1);
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+
+            close_account(scheduled_info, signer)?;
+            prose!(config.params, "Cancelled scheduled claim {}, refunded {} points", scheduled.id, scheduled.required_points);
+        }
+
+        RewardInstruction::OpenStream { required_points, total_amount, duration } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let user_token_account = next_named_account(accounts_iter, "user token")?;
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let stream_info = next_account_info(accounts_iter)?;
+            if config.params.require_kyc || config.params.require_memo {
+                verify_claim_siblings(&config, signer.key, next_account_info(accounts_iter)?)?;
+            }
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+
+            // The stream can never be worth more than the points spent on it
+            if duration == 0 || total_amount == 0 || total_amount > claim_amount(&config, required_points)? {
+                prose!(config.params, "Stream must last at least a second and pay at most the value of the spent points");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let id = reward_account.next_schedule_id;
+            let (stream_pda, stream_bump) = find_stream_address(program_id, signer.key, id);
+            if stream_info.key != &stream_pda {
+                prose!(config.params, "Invalid stream PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+
+            validate_payout_accounts(&config, signer.key, user_token_account, vault_token_account)?;
+
+            // Open streams count against the same limit as scheduled claims
+            let max_scheduled = config.params.max_scheduled_claims;
+            if max_scheduled != 0 && reward_account.scheduled_claims >= max_scheduled {
+                prose!(config.params, "User already has {} scheduled claims", reward_account.scheduled_claims);
+                return Err(RewardError::TooManyScheduledClaims.into());
+            }
+
+            debit_claim(&mut reward_account, &mut config, required_points)?;
+            reward_account.next_schedule_id = id.checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.scheduled_claims = reward_account.scheduled_claims.checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+            // The signer pays rent for the stream until it is drained or cancelled
+            create_pda_account(
+                signer,
+                stream_info,
+                REWARD_STREAM_SIZE,
+                program_id,
+                system_program,
+                &[STREAM_SEED, signer.key.as_ref(), &id.to_le_bytes(), &[stream_bump]],
+            )?;
+
+            let stream = RewardStream {
+                user: *signer.key,
+                id,
+                destination: *user_token_account.key,
+                required_points: required_points.into(),
+                total_amount,
+                withdrawn: 0,
+                start_ts: Clock::get()?.unix_timestamp,
+                duration,
+            };
+            stream.write_account_data(&mut stream_info.data.borrow_mut())?;
+            prose!(config.params, "Opened stream {} of {} WAGUS tokens over {} seconds", id, total_amount, duration);
+        }
+
+        RewardInstruction::WithdrawStream => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let TokenAccounts { user_token_account, vault_token_account, mint_account, token_program } = next_token_accounts(accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let stream_info = next_account_info(accounts_iter)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+
+            let mut stream = load_stream(program_id, &config, stream_info)?;
+            if stream.user != *signer.key || user_token_account.key != &stream.destination {
+                prose!(config.params, "Accounts do not match the stream");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            // The destination was checked when the stream opened, but the vault is passed anew each time
+            validate_payout_accounts(&config, signer.key, user_token_account, vault_token_account)?;
+            let decimals = mint_decimals(&config, mint_account)?;
+
+            let amount = streamed_amount(&stream)?.saturating_sub(stream.withdrawn);
+            if amount == 0 {
+                prose!(config.params, "Nothing has accrued on stream {} since the last withdrawal", stream.id);
+                return Ok(());
+            }
+            pay_from_vault(
+                program_id,
+                &config,
+                token_program,
+                vault_token_account,
+                user_token_account,
+                vault_authority_info,
+                amount,
+            )?;
+            stream.withdrawn += amount;
+
+            // A fully paid stream is closed and stops counting against the user
+            if stream.withdrawn == stream.total_amount {
+                reward_account.scheduled_claims = reward_account.scheduled_claims.saturating_sub(1);
+                reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+                close_account(stream_info, signer)?;
+            } else {
+                stream.write_account_data(&mut stream_info.data.borrow_mut())?;
+            }
+
+            emit_event(&config, &RewardEvent::Claimed {
+                user: stream.user,
+                required_points: 0,
+                amount,
+                decimals,
+            })?;
+            prose!(config.params, "Transferred {} WAGUS tokens from stream {}", amount, stream.id);
+        }
+
+        RewardInstruction::CancelStream => {
+            let user_token_account = next_named_account(accounts_iter, "user token")?;
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let token_program = next_token_program(accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let stream_info = next_account_info(accounts_iter)?;
+            let user_info = next_account_info(accounts_iter)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+            let user_reward_info = next_account_info(accounts_iter)?;
+
+            let stream = load_stream(program_id, &config, stream_info)?;
+            let canceller = if config.params.admin_cancels_streams { config.admin } else { stream.user };
+            if *signer.key != canceller {
+                prose!(config.params, "Only {} can cancel this stream", canceller);
+                return Err(ProgramError::IncorrectAuthority);
+            }
+            if user_info.key != &stream.user
+                || user_token_account.key != &stream.destination
+                || reward_address_bump(program_id, &stream.user, user_reward_info).is_none()
+            {
+                prose!(config.params, "Accounts do not match the stream");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let mut user_reward = RewardAccount::try_from_account_data(&user_reward_info.data.borrow())?;
+            validate_payout_accounts(&config, &stream.user, user_token_account, vault_token_account)?;
+
+            // Pay out what has accrued, then refund the unstreamed share of the points
+            let accrued = streamed_amount(&stream)?;
+            let amount = accrued.saturating_sub(stream.withdrawn);
+            if amount > 0 {
+                pay_from_vault(
+                    program_id,
+                    &config,
+                    token_program,
+                    vault_token_account,
+                    user_token_account,
+                    vault_authority_info,
+                    amount,
+                )?;
+            }
+            let refund = (stream.required_points as u128 * (stream.total_amount - accrued) as u128
+                / stream.total_amount as u128) as u64;
+
+            user_reward.total_points = user_reward.total_points.checked_add(refund)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            user_reward.scheduled_claims = user_reward.scheduled_claims.saturating_sub(1);
+            user_reward.write_account_data(&mut user_reward_info.data.borrow_mut())?;
+
+            close_account(stream_info, user_info)?;
+            prose!(config.params, "Cancelled stream {}, paid {} WAGUS tokens and refunded {} points", stream.id, amount, refund);
+        }
+
+        RewardInstruction::Archive => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // There is deliberately no instruction that clears the flag again
+            config.archived = true;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Program archived, only reads are served from now on");
+        }
+
+        RewardInstruction::CloseReward { force } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            // The rent goes to an optional destination after the config, defaulting to the signer
+            let destination = accounts_iter.next().unwrap_or(signer);
+
+            if reward_account_info.owner != program_id {
+                prose!(config.params, "Reward account is not owned by the program");
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            // The account belongs to the wallet in its seeds; an Earn authority cannot close it
+            let reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            if reward_account.owner != *signer.key {
+                prose!(config.params, "Only the owner {} can close the account", reward_account.owner);
+                return Err(ProgramError::IncorrectAuthority);
+            }
+            // Scheduled claims refund into and pay out against this account
+            if reward_account.scheduled_claims > 0 {
+                prose!(config.params, "Cancel or execute the {} scheduled claims first", reward_account.scheduled_claims);
+                return Err(RewardError::RewardAccountNotEmpty.into());
+            }
+            // Staked and pending points are forfeited along with the spendable ones
+            let outstanding = reward_account.total_points
+                .saturating_add(reward_account.staked_points)
+                .saturating_add(reward_account.pending_points);
+            if outstanding > 0 && !force {
+                prose!(config.params, "Reward account still holds {} points, force to forfeit them", outstanding);
+                return Err(RewardError::RewardAccountNotEmpty.into());
+            }
+
+            let lamports = close_account(reward_account_info, destination)?;
+            prose!(config.params, "Reward account closed, {} lamports returned to {}", lamports, destination.key);
+        }
+
+        RewardInstruction::SetGuardians { guardians, threshold } => {
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            let guardian_set_info = next_account_info(accounts_iter)?;
+            let (guardian_set_pda, guardian_set_bump) = find_guardian_set_address(program_id, signer.key);
+            if guardian_set_info.key != &guardian_set_pda {
+                prose!(config.params, "Invalid guardian set PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+
+            // A user cannot be their own guardian, and each guardian counts once
+            let distinct = guardians.iter().enumerate().all(|(index, guardian)| {
+                guardian != signer.key && !guardians[..index].contains(guardian)
+            });
+            if guardians.len() > MAX_GUARDIANS || threshold == 0 || threshold as usize > guardians.len() || !distinct {
+                prose!(config.params, "Expected up to {} distinct guardians and a threshold between 1 and their count", MAX_GUARDIANS);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // The signer pays rent for the guardian set the first time it is stored
+            if guardian_set_info.data_is_empty() {
+                create_pda_account(
+                    signer,
+                    guardian_set_info,
+                    GUARDIAN_SET_SIZE,
+                    program_id,
+                    system_program,
+                    &[GUARDIANS_SEED, signer.key.as_ref(), &[guardian_set_bump]],
+                )?;
+            }
+
+            let mut guardian_set = GuardianSet {
+                user: *signer.key,
+                threshold,
+                guardian_count: guardians.len() as u8,
+                ..GuardianSet::default()
+            };
+            guardian_set.guardians[..guardians.len()].copy_from_slice(&guardians);
+            guardian_set.write_account_data(&mut guardian_set_info.data.borrow_mut())?;
+            prose!(config.params, "Stored {} guardians with a threshold of {}", guardians.len(), threshold);
+        }
+
+        RewardInstruction::RecoverOwnership { user, new_owner } => {
+            // Guardians sign for the user whose reward account they recover
+            let (reward_account_info, _) = next_reward_account(program_id, &user, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            // Points cannot be moved out of reach of a pause, by the admin or on either account
+            require_not_halted(&config)?;
+            let guardian_set_info = next_account_info(accounts_iter)?;
+            let new_reward_info = next_account_info(accounts_iter)?;
+
+            let (guardian_set_pda, _guardian_set_bump) = find_guardian_set_address(program_id, &user);
+            if guardian_set_info.key != &guardian_set_pda
+                || guardian_set_info.owner != program_id
+                || reward_address_bump(program_id, &new_owner, new_reward_info).is_none()
+                || new_owner == user
+            {
+                prose!(config.params, "Accounts do not match the recovery");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let guardian_set = GuardianSet::try_from_account_data(&guardian_set_info.data.borrow())?;
+
+            // The signer and any trailing signing accounts count once per guardian
+            let guardians = &guardian_set.guardians[..guardian_set.guardian_count as usize];
+            let mut approvals: Vec<&Pubkey> = core::iter::once(signer)
+                .chain(accounts_iter)
+                .filter(|info| info.is_signer && guardians.contains(info.key))
+                .map(|info| info.key)
+                .collect();
+            approvals.sort();
+            approvals.dedup();
+            if approvals.len() < guardian_set.threshold as usize {
+                prose!(config.params, "{} of {} required guardians signed", approvals.len(), guardian_set.threshold);
+                return Err(RewardError::GuardianThresholdNotMet.into());
+            }
+
+            // Spendable points move to the new owner, who must have run Init; history stays behind
+            let mut old_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            let mut new_account = RewardAccount::try_from_account_data(&new_reward_info.data.borrow())?;
+            require_unpaused(&config, &old_account)?;
+            require_unpaused(&config, &new_account)?;
+            require_same_mint(&config, &old_account, &new_account)?;
+            let moved = old_account.total_points;
+            new_account.total_points = new_account.total_points.checked_add(old_account.total_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            new_account.staked_points = new_account.staked_points.checked_add(old_account.staked_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            new_account.pending_points = new_account.pending_points.checked_add(old_account.pending_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            old_account.total_points = 0;
+            old_account.staked_points = 0;
+            old_account.pending_points = 0;
+            old_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            new_account.write_account_data(&mut new_reward_info.data.borrow_mut())?;
+            prose!(config.params, "Recovered {} points of {} to {}", moved, user, new_owner);
+        }
+
+        RewardInstruction::SetPaused { user, paused } => {
+            // The config's current earn authority or the admin pauses the given user's reward
+            // account, so rotating the earn authority through UpdateConfig carries over to it
+            let (reward_account_info, _) = next_reward_account(program_id, &user, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            if *signer.key != config.params.earn_authority && *signer.key != config.admin {
+                prose!(config.params, "Only the earn authority {} or the admin can pause the account", config.params.earn_authority);
+                return Err(ProgramError::IncorrectAuthority);
+            }
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+
+            reward_account.paused = paused;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            prose!(config.params, "Reward account of {} paused: {}", user, paused);
+        }
+
+        RewardInstruction::UpdateConfig { params } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            validate_params(&params)?;
+
+            // Rate changes go through SetExchangeRate so users get the timelock's notice
+            settle_exchange_rate(&mut config)?;
+            if params.exchange_rate != config.params.exchange_rate {
+                prose!(config.params, "Change the exchange rate with SetExchangeRate");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // A shorter timelock would let the next SetExchangeRate skip the notice users were promised
+            if params.rate_change_timelock_secs < config.params.rate_change_timelock_secs {
+                prose!(config.params, "Rate change timelock cannot drop below {} seconds", config.params.rate_change_timelock_secs);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            config.params = params;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Config parameters updated");
+        }
+
+        RewardInstruction::SetMintingPaused { paused } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            config.minting_paused = paused;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Minting paused: {}", paused);
+        }
+
+        RewardInstruction::SetProgramPaused { paused } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            config.program_paused = paused;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Program paused: {}", paused);
+        }
+
+        RewardInstruction::ProposeAdmin { new_admin } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // The admin only changes once the nominee proves it controls the key
+            config.pending_admin = new_admin;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Proposed {} as the next admin", new_admin);
+        }
+
+        RewardInstruction::AcceptAdmin => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+            if config.pending_admin == Pubkey::default() || config.pending_admin != *signer.key {
+                prose!(config.params, "Signer {} is not the pending admin", signer.key);
+                return Err(ProgramError::IncorrectAuthority);
+            }
+
+            let previous = config.admin;
+            config.admin = config.pending_admin;
+            config.pending_admin = Pubkey::default();
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Admin transferred from {} to {}", previous, config.admin);
+        }
+
+        RewardInstruction::SetNote { note } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+
+            // A first-release account is upgraded so there is room for the note
+            migrate_reward_account(program_id, &config, reward_account_info, signer, None, Some(system_program))?;
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+
+            // Stored as given; encrypting it is up to the client
+            reward_account.note = note;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            prose!(config.params, "Note updated");
+        }
+
+        RewardInstruction::PoolPoints { amount } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let pool_owner = next_account_info(accounts_iter)?;
+            let pool_info = next_account_info(accounts_iter)?;
+
+            // Only the member signs; the pool is any other initialized reward account
+            if reward_address_bump(program_id, pool_owner.key, pool_info).is_none() {
+                prose!(config.params, "Invalid pool reward account PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+            if pool_owner.key == signer.key {
+                prose!(config.params, "Cannot pool points into the signer's own account");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if amount == 0 {
+                prose!(config.params, "Pooled amount must be greater than zero");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let mut member = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            let mut pool = RewardAccount::try_from_account_data(&pool_info.data.borrow())?;
+            require_unpaused(&config, &member)?;
+            require_unpaused(&config, &pool)?;
+            require_same_mint(&config, &member, &pool)?;
+
+            // Spendable points move; lifetime history stays with the member
+            member.total_points = member.total_points.checked_sub(amount as u64).ok_or_else(|| {
+                prose!(config.params, "Cannot pool {} of {} points", amount, member.total_points);
+                RewardError::NotEnoughPoints
+            })?;
+            pool.total_points = pool.total_points.checked_add(amount as u64)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            member.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            pool.write_account_data(&mut pool_info.data.borrow_mut())?;
+            prose!(config.params, "Pooled {} points into {}", amount, pool_owner.key);
+        }
+
+        RewardInstruction::ClaimSigned { amount, nonce, expiry_ts, signature } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let TokenAccounts { user_token_account, vault_token_account, mint_account, token_program } = next_token_accounts(accounts_iter)?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+            let instructions_sysvar = next_account_info(accounts_iter)?;
+            let nonce_registry_info = next_account_info(accounts_iter)?;
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+
+            // The oracle signs (user, amount, nonce, expiry_ts) in an ed25519 instruction right before this one
+            if config.params.oracle == Pubkey::default() {
+                prose!(config.params, "No oracle is configured");
+                return Err(RewardError::InvalidOracleSignature.into());
+            }
+            // Regulated deployments need the same KYC attestation and/or memo as a plain claim
+            verify_claim_siblings(&config, signer.key, instructions_sysvar)?;
+            let signed = preceding_ed25519_signature(instructions_sysvar)?;
+            let valid = signed.is_some_and(|signed| {
+                signed.public_key == config.params.oracle.as_ref()
+                    && signed.signature == signature
+                    && signed.message == oracle_claim_message(signer.key, amount, nonce, expiry_ts)
+            });
+            if !valid {
+                prose!(config.params, "Invalid oracle signature for {}", signer.key);
+                return Err(RewardError::InvalidOracleSignature.into());
+            }
+            if Clock::get()?.unix_timestamp > expiry_ts {
+                prose!(config.params, "Oracle signature expired at {}", expiry_ts);
+                return Err(RewardError::OracleSignatureExpired.into());
+            }
+
+            // Each authorization pays out once: its nonce is retired in the signer's registry,
+            // which oracle claims share with vouchers. Signatures are malleable, nonces are not
+            consume_nonce(program_id, &config, signer, nonce_registry_info, system_program, nonce)?;
+
+            validate_payout_accounts(&config, signer.key, user_token_account, vault_token_account)?;
+            let decimals = mint_decimals(&config, mint_account)?;
+
+            throttle_claim(&mut reward_account, &config)?;
+            reward_account.rewards_claimed = reward_account.rewards_claimed.checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+            pay_from_vault(
+                program_id,
+                &config,
+                token_program,
+                vault_token_account,
+                user_token_account,
+                vault_authority_info,
+                amount,
+            )?;
+
+            emit_event(&config, &RewardEvent::Claimed {
+                user: *signer.key,
+                required_points: 0,
+                amount,
+                decimals,
+            })?;
+            prose!(config.params, "Transferred {} WAGUS tokens for an oracle-signed claim", amount);
+        }
+
+        RewardInstruction::SetExchangeRate { rate, effective_ts } => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            if rate == 0 {
+                prose!(config.params, "Exchange rate must be greater than zero");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Users get at least the timelock's notice before the rate changes under them
+            let earliest = Clock::get()?.unix_timestamp
+                .saturating_add(config.params.rate_change_timelock_secs as i64);
+            if effective_ts < earliest {
+                prose!(config.params, "Exchange rate change cannot take effect before {}", earliest);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            settle_exchange_rate(&mut config)?;
+            config.pending_exchange_rate = rate;
+            config.pending_rate_effective_ts = effective_ts;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Exchange rate {} takes effect at {}", rate, effective_ts);
+        }
+
+        RewardInstruction::CancelPendingRate => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+
+            // A rate already in effect stays; only a queued one can be dropped
+            settle_exchange_rate(&mut config)?;
+            if config.pending_exchange_rate == 0 {
+                prose!(config.params, "No pending exchange rate to cancel");
+                return Err(ProgramError::InvalidArgument);
+            }
+            config.pending_exchange_rate = 0;
+            config.pending_rate_effective_ts = 0;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Pending exchange rate cancelled");
+        }
+
+        RewardInstruction::SetReferralCode { code, referrer } => {
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            let referral_info = next_account_info(accounts_iter)?;
+
+            let (referral_pda, referral_bump) = find_referral_address(program_id, &code);
+            if referral_info.key != &referral_pda {
+                prose!(config.params, "Invalid referral code PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+            // The first registration creates the PDA, later ones repoint it
+            if referral_info.data_is_empty() {
+                create_pda_account(
+                    signer,
+                    referral_info,
+                    REFERRAL_CODE_SIZE,
+                    program_id,
+                    system_program,
+                    &[REFERRAL_SEED, &code, &[referral_bump]],
+                )?;
+            }
+            ReferralCode { code, referrer }.write_account_data(&mut referral_info.data.borrow_mut())?;
+            prose!(config.params, "Referral code now credits {}", referrer);
+        }
+
+        RewardInstruction::Heartbeat => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_admin(&config, signer)?;
+            // The pause exists to wait for the admin, so it does not block this
+            if config.archived {
+                prose!(config.params, "Program is archived");
+                return Err(RewardError::ProgramArchived.into());
+            }
+
+            config.last_heartbeat_ts = Clock::get()?.unix_timestamp;
+            config.dead_man_paused = false;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "Admin heartbeat at {}", config.last_heartbeat_ts);
+        }
+
+        RewardInstruction::TriggerDeadManPause => {
+            let config_info = next_account_info(accounts_iter)?;
+            let mut config = load_config(program_id, config_info)?;
+            require_active(&config)?;
+
+            let timeout = config.params.heartbeat_timeout_secs;
+            let deadline = config.last_heartbeat_ts.saturating_add(timeout as i64);
+            if timeout == 0 || Clock::get()?.unix_timestamp < deadline {
+                prose!(config.params, "Admin heartbeat is still valid");
+                return Err(RewardError::HeartbeatNotExpired.into());
+            }
+
+            config.dead_man_paused = true;
+            config.write_account_data(&mut config_info.data.borrow_mut())?;
+            prose!(config.params, "No admin heartbeat since {}, program paused", config.last_heartbeat_ts);
+        }
+
+        RewardInstruction::MergeAccounts => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let target_owner = next_account_info(accounts_iter)?;
+            let target_info = next_account_info(accounts_iter)?;
+
+            // The owner of both wallets signs for each of them
+            if !target_owner.is_signer {
+                prose!(config.params, "Merging requires a signature from {}", target_owner.key);
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if reward_address_bump(program_id, target_owner.key, target_info).is_none() {
+                prose!(config.params, "Invalid target reward account PDA");
+                return Err(RewardError::InvalidPda.into());
+            }
+            if target_owner.key == signer.key {
+                prose!(config.params, "Cannot merge a reward account into itself");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let source = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            let mut target = RewardAccount::try_from_account_data(&target_info.data.borrow())?;
+            require_unpaused(&config, &source)?;
+            require_unpaused(&config, &target)?;
+            require_same_mint(&config, &source, &target)?;
+            // Scheduled claims refund into and pay out against the source account
+            if source.scheduled_claims > 0 {
+                prose!(config.params, "Cancel or execute the {} scheduled claims first", source.scheduled_claims);
+                return Err(RewardError::RewardAccountNotEmpty.into());
+            }
+
+            target.total_points = target.total_points.checked_add(source.total_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.lifetime_points = target.lifetime_points.checked_add(source.lifetime_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.staked_points = target.staked_points.checked_add(source.staked_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.pending_points = target.pending_points.checked_add(source.pending_points)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.rewards_claimed = target.rewards_claimed.checked_add(source.rewards_claimed)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.dust_credit = target.dust_credit.checked_add(source.dust_credit)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            target.write_account_data(&mut target_info.data.borrow_mut())?;
+
+            let lamports = close_account(reward_account_info, signer)?;
+            prose!(config.params, "Merged {} points into {}, {} lamports returned", source.total_points, target_owner.key, lamports);
+        }
+
+        RewardInstruction::Burn { amount } => {
+            let source_token_account = next_named_account(accounts_iter, "source token")?;
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            let token_program = next_token_program(accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            if *mint_account.key != config.mint {
+                prose!(config.params, "Mint does not match the reward mint {}", config.mint);
+                return Err(RewardError::WrongMint.into());
+            }
+
+            // Any token account of the mint may be burned from; the program's own vault is
+            // burned by the admin through the vault authority PDA, anything else by its owner
+            let source = TokenAccount::unpack(&source_token_account.data.borrow())?;
+            let vault_authority_pda = vault_authority_address(program_id, &config)?;
+            let burn_ix = |authority: &Pubkey| burn(
+                token_program.key,
+                source_token_account.key,
+                mint_account.key,
+                authority,
+                &[],
+                amount,
+            );
+            if source.owner == vault_authority_pda {
+                require_admin(&config, signer)?;
+                let vault_authority_info = next_account_info(accounts_iter)?;
+                if vault_authority_info.key != &vault_authority_pda {
+                    prose!(config.params, "Invalid vault authority PDA");
+                    return Err(RewardError::InvalidPda.into());
+                }
+                invoke_signed(
+                    &burn_ix(vault_authority_info.key)?,
+                    &[
+                        source_token_account.clone(),
+                        mint_account.clone(),
+                        vault_authority_info.clone(),
+                        token_program.clone(),
+                    ],
+                    &[&[VAULT_AUTHORITY_SEED, &[config.vault_authority_bump]]],
+                )?;
+            } else {
+                if source.owner != *signer.key {
+                    prose!(config.params, "Only {} can burn from this token account", source.owner);
+                    return Err(ProgramError::IncorrectAuthority);
+                }
+                invoke(
+                    &burn_ix(signer.key)?,
+                    &[
+                        source_token_account.clone(),
+                        mint_account.clone(),
+                        signer.clone(),
+                        token_program.clone(),
+                    ],
+                )?;
+            }
+            prose!(config.params, "Burned {} WAGUS tokens from {}", amount, source_token_account.key);
+        }
+
+        RewardInstruction::RescueTokens { amount } => {
+            let destination_token_account = next_named_account(accounts_iter, "destination token")?;
+            let source_token_account = next_named_account(accounts_iter, "source token")?;
+            let token_program = next_token_program(accounts_iter)?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+
+            // Tokens of any other mint held by the vault authority go to the destination;
+            // the reward vault itself is only ever paid out through claims
+            let source = TokenAccount::unpack(&source_token_account.data.borrow())?;
+            if source.mint == config.mint || *source_token_account.key == find_vault_address(program_id, &config.mint).0 {
+                prose!(config.params, "Cannot rescue from the reward vault");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let vault_authority_pda = vault_authority_address(program_id, &config)?;
+            if vault_authority_info.key != &vault_authority_pda || source.owner != vault_authority_pda {
+                prose!(config.params, "Only token accounts of the vault authority PDA can be rescued");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            invoke_signed(
+                &transfer(
+                    token_program.key,
+                    source_token_account.key,
+                    destination_token_account.key,
+                    vault_authority_info.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    source_token_account.clone(),
+                    destination_token_account.clone(),
+                    vault_authority_info.clone(),
+                    token_program.clone(),
+                ],
+                &[&[VAULT_AUTHORITY_SEED, &[config.vault_authority_bump]]],
+            )?;
+            prose!(config.params, "Rescued {} tokens of mint {} to {}", amount, source.mint, destination_token_account.key);
+        }
+
+        RewardInstruction::EarnWithVoucher { points, nonce, expiry_ts, signature } => {
+            let (reward_account_info, _) = next_reward_account(program_id, signer.key, accounts_iter)?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_active(&config)?;
+            require_not_halted(&config)?;
+            let instructions_sysvar = next_account_info(accounts_iter)?;
+            let nonce_registry_info = next_account_info(accounts_iter)?;
+
+            migrate_reward_account(program_id, &config, reward_account_info, signer, None, Some(system_program))?;
+            let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+            require_unpaused(&config, &reward_account)?;
+
+            // The earn authority signs (user, points, nonce, expiry_ts) in an ed25519 instruction right before this one
+            let signed = preceding_ed25519_signature(instructions_sysvar)?;
+            let valid = signed.is_some_and(|signed| {
+                signed.public_key == config.params.earn_authority.as_ref()
+                    && signed.signature == signature
+                    && signed.message == earn_voucher_message(signer.key, points, nonce, expiry_ts)
+            });
+            if !valid {
+                prose!(config.params, "Invalid earn voucher for {}", signer.key);
+                return Err(RewardError::InvalidVoucher.into());
+            }
+            if Clock::get()?.unix_timestamp > expiry_ts {
+                prose!(config.params, "Earn voucher expired at {}", expiry_ts);
+                return Err(RewardError::VoucherExpired.into());
+            }
+
+            consume_nonce(program_id, &config, signer, nonce_registry_info, system_program, nonce)?;
+
+            credit_earn(&config, reward_account_info, &mut reward_account, signer.key, points)?;
+        }
+
+        RewardInstruction::InitVault => {
+            let vault_token_account = next_named_account(accounts_iter, "vault token")?;
+            let mint_account = next_named_account(accounts_iter, "mint")?;
+            let token_program = next_token_program(accounts_iter)?;
+            let system_program = next_named_account(accounts_iter, "system program")?;
+            let config = load_config(program_id, next_account_info(accounts_iter)?)?;
+            require_admin(&config, signer)?;
+            require_active(&config)?;
+            let vault_authority_info = next_account_info(accounts_iter)?;
+
+            if *mint_account.key != config.mint {
+                prose!(config.params, "Vaults can only be created for the reward mint {}", config.mint);
+                return Err(RewardError::WrongMint.into());
+            }
+            let (vault_pda, vault_bump) = find_vault_address(program_id, mint_account.key);
+            if vault_token_account.key != &vault_pda {
+                prose!(config.params, "Invalid vault PDA");
+                return Err(RewardError::InvalidVault.into());
+            }
+            if vault_authority_info.key != &vault_authority_address(program_id, &config)? {
+                prose!(config.params, "Invalid vault authority PDA");
+                return Err(RewardError::InvalidVault.into());
+            }
+            if !vault_token_account.data_is_empty() {
+                prose!(config.params, "Vault already exists");
+                return Err(RewardError::AlreadyInitialized.into());
+            }
+
+            create_pda_account(
+                signer,
+                vault_token_account,
+                TokenAccount::LEN,
+                &spl_token::id(),
+                system_program,
+                &[VAULT_SEED, mint_account.key.as_ref(), &[vault_bump]],
+            )?;
+            invoke(
+                &initialize_account3(
+                    token_program.key,
+                    vault_token_account.key,
+                    mint_account.key,
+                    vault_authority_info.key,
+                )?,
+                &[vault_token_account.clone(), mint_account.clone(), token_program.clone()],
+            )?;
+            prose!(config.params, "Vault {} created for mint {}", vault_token_account.key, mint_account.key);
+        }
+    }
+
+    Ok(())
+}
+
+// Reject parameter sets InitConfig and UpdateConfig cannot accept
+fn validate_params(params: &ConfigParams) -> ProgramResult {
+    if params.exchange_rate == 0 {
+        prose!(params, "Exchange rate must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if params.max_vault_fraction_bps > BPS_DENOMINATOR {
+        prose!(params, "Vault fraction cannot exceed {} bps", BPS_DENOMINATOR);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if params.cohort_count as usize > MAX_COHORTS {
+        prose!(params, "At most {} cohorts are supported", MAX_COHORTS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    // A configured cohort earning nothing would silently swallow its members' points
+    if params.cohort_earn_bps[..params.cohort_count as usize].contains(&0) {
+        prose!(params, "Every configured cohort needs a non-zero earn share");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if params.earn_authority == Pubkey::default() {
+        prose!(params, "An earn authority is required");
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+// Ensure the signer is the program's upgrade authority, read from its ProgramData account
+fn require_upgrade_authority(program_id: &Pubkey, params: &ConfigParams, signer: &AccountInfo, program_data_info: &AccountInfo) -> ProgramResult {
+    let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data_info.key != program_data || *program_data_info.owner != bpf_loader_upgradeable::id() {
+        prose!(params, "Invalid program data account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // ProgramData is a u32 variant tag (3), the deploy slot, then an optional upgrade authority
+    let data = program_data_info.data.borrow();
+    let upgrade_authority = match data.get(..45) {
+        Some(header) if header[..4] == 3u32.to_le_bytes() && header[12] == 1 => &header[13..45],
+        _ => {
+            prose!(params, "Program has no upgrade authority");
+            return Err(ProgramError::IncorrectAuthority);
+        }
+    };
+    if upgrade_authority != signer.key.as_ref() {
+        prose!(params, "Signer {} is not the program's upgrade authority", signer.key);
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+// Ensure the signer is the admin recorded in the config
+fn require_admin(config: &Config, signer: &AccountInfo) -> ProgramResult {
+    if config.admin != *signer.key {
+        prose!(config.params, "Signer {} is not the config admin", signer.key);
+        return Err(ProgramError::IncorrectAuthority);
+    }
+    Ok(())
+}
+
+// Points only move between reward accounts of the same mint
+fn require_same_mint(config: &Config, from: &RewardAccount, to: &RewardAccount) -> ProgramResult {
+    if from.mint != to.mint {
+        prose!(config.params, "Cannot move points of mint {} into an account of mint {}", from.mint, to.mint);
+        return Err(RewardError::WrongMint.into());
+    }
+    Ok(())
+}
+
+// Reject earning and claiming on an account its authority paused
+fn require_unpaused(config: &Config, reward_account: &RewardAccount) -> ProgramResult {
+    if reward_account.paused {
+        prose!(config.params, "Reward account is paused");
+        return Err(RewardError::Paused.into());
+    }
+    Ok(())
+}
+
+// Reject mutations once the admin archived the program, or while the dead-man's switch holds it paused
+fn require_active(config: &Config) -> ProgramResult {
+    if config.archived {
+        prose!(config.params, "Program is archived");
+        return Err(RewardError::ProgramArchived.into());
+    }
+    if config.dead_man_paused {
+        prose!(config.params, "Program is paused until the admin sends a Heartbeat");
+        return Err(RewardError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+// Reject earning, minting and anything that spends points or pays out of the vault
+// while the admin has the program paused
+fn require_not_halted(config: &Config) -> ProgramResult {
+    if config.program_paused {
+        prose!(config.params, "Program is paused by the admin");
+        return Err(RewardError::AdminPaused.into());
+    }
+    Ok(())
+}
+
+// The fields of RewardInstruction::Init
+struct InitArgs {
+    idempotency_key: Option<[u8; 32]>,
+    referral_code: Option<[u8; 8]>,
+    cohort: u8,
+}
+
+// Init: signer, reward PDA, mint, system program (left out when reinitializing), config,
+// then the optional vault, creator stats, referral code and rent payer accounts
+fn process_init<'a, 'b>(
+    program_id: &Pubkey,
+    signer: &'a AccountInfo<'b>,
+    reward_account_info: &'a AccountInfo<'b>,
+    reward_bump: u8,
+    args: InitArgs,
+    accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'b>>,
+) -> ProgramResult {
+    let InitArgs { idempotency_key, referral_code, cohort } = args;
+    let mint_account = next_named_account(accounts_iter, "mint")?;
+    // Only creating the account needs the system program, so reinitializing may leave it out
+    let system_program = match accounts_iter.as_slice().first() {
+        Some(info) if system_program::check_id(info.key) => accounts_iter.next(),
+        _ => None,
+    };
+    let config_info = next_named_account(accounts_iter, "config")?;
+    let mut config = load_config(program_id, config_info)?;
+    if idempotency_key == Some([0; 32]) {
+        prose!(config.params, "Idempotency key must not be all zeroes");
+        return Err(ProgramError::InvalidArgument);
+    }
+    require_active(&config)?;
+    // The vault is only read when new users must wait for it to be funded
+    let vault_token_account = match config.params.require_funded_vault {
+        true => Some(next_named_account(accounts_iter, "vault token")?),
+        false => None,
+    };
+    // With a creation cap, the rent payer's creator stats PDA follows
+    let creator_stats_info = match config.params.max_accounts_per_creator {
+        0 => None,
+        _ => Some(next_named_account(accounts_iter, "creator stats")?),
+    };
+    // A referral code is looked up in its PDA
+    let referral_info = match referral_code {
+        Some(_) => Some(next_named_account(accounts_iter, "referral code")?),
+        None => None,
+    };
+    // A relayer sponsoring the signup may follow and pay the rent; the account is still the signer's
+    let payer = match accounts_iter.next() {
+        Some(info) if info.is_signer => info,
+        Some(info) => {
+            prose!(config.params, "Rent payer {} must sign", info.key);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        None => signer,
+    };
+
+    // Check if the account is already initialized, upgrading an older layout instead of resetting it
+    if !reward_account_info.data_is_empty() {
+        if migrate_reward_account(program_id, &config, reward_account_info, signer, Some(mint_account), system_program)? {
+            prose!(config.params, "Migrated existing reward account to layout version {}", REWARD_ACCOUNT_VERSION);
+            return Ok(());
+        }
+        // A retry of the Init that created the account succeeds without doing anything
+        if let Some(key) = idempotency_key {
+            if RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?.init_key == key {
+                prose!(config.params, "Reward account already initialized with this key");
+                return Ok(());
+            }
+        }
+        prose!(config.params, "Account already initialized");
+        return Err(RewardError::AlreadyInitialized.into());
+    }
+    let system_program = system_program.ok_or_else(|| missing_account("system program"))?;
+    // Claims only pay out in the config's mint, recorded here too
+    if *mint_account.key != config.mint {
+        prose!(config.params, "Mint does not match the reward mint {}", config.mint);
+        return Err(RewardError::WrongMint.into());
+    }
+
+    // Optionally refuse new users until the vault can pay out claims
+    if let Some(vault_token_account) = vault_token_account {
+        if !vault_funded(program_id, &config, vault_token_account)? {
+            return Err(RewardError::VaultNotFunded.into());
+        }
+    }
+    // Capped betas stop taking signups after max_total_users accounts
+    let max_users = config.params.max_total_users;
+    if max_users != 0 && config.total_users >= max_users {
+        prose!(config.params, "Signups are closed after {} users", max_users);
+        return Err(RewardError::SignupCapReached.into());
+    }
+    // Without cohorts every account lands in cohort 0
+    if cohort >= config.params.cohort_count.max(1) {
+        prose!(config.params, "Cohort {} is not one of the {} configured", cohort, config.params.cohort_count);
+        return Err(RewardError::UnknownCohort.into());
+    }
+    let referrer = match (referral_code, referral_info) {
+        (Some(code), Some(referral_info)) => resolve_referral(program_id, &config, &code, referral_info)?,
+        _ => Pubkey::default(),
+    };
+    if let Some(creator_stats_info) = creator_stats_info {
+        record_account_creation(program_id, &config, payer, creator_stats_info, system_program)?;
+    }
+    config.total_users = config.total_users.saturating_add(1);
+    config.write_account_data(&mut config_info.data.borrow_mut())?;
+
+    // Create the reward PDA, with the payer covering rent (storage fees)
+    create_pda_account(
+        payer,
+        reward_account_info,
+        REWARD_ACCOUNT_SIZE,
+        program_id,
+        system_program,
+        &[REWARD_SEED, signer.key.as_ref(), &[reward_bump]],
+    )?;
+
+    // Initialize the reward account with default values
+    let reward_account = RewardAccount {
+        mint: *mint_account.key,
+        authority: config.params.earn_authority,
+        init_key: idempotency_key.unwrap_or_default(),
+        referrer,
+        cohort,
+        bump: reward_bump,
+        owner: *signer.key,
+        ..RewardAccount::default()
+    };
+    reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+    prose!(config.params, "Reward account initialized!");
+    Ok(())
+}
+
+// Earn: signer, reward PDA, system program (left out for current layouts), config
+// and the authority co-signer
+fn process_earn<'a, 'b>(
+    program_id: &Pubkey,
+    signer: &'a AccountInfo<'b>,
+    reward_account_info: &'a AccountInfo<'b>,
+    points: u32,
+    accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'b>>,
+) -> ProgramResult {
+    // Only upgrading an older layout needs the system program, so current accounts may leave it out
+    let system_program = match accounts_iter.as_slice().first() {
+        Some(info) if system_program::check_id(info.key) => accounts_iter.next(),
+        _ => None,
+    };
+    let config = load_config(program_id, next_named_account(accounts_iter, "config")?)?;
+    require_active(&config)?;
+    require_not_halted(&config)?;
+    let authority = next_named_account(accounts_iter, "authority")?;
+
+    // Accounts of an older layout are upgraded before being read; the mint-less one of
+    // the old lib.rs has to go through MigrateLegacyLayout first
+    migrate_reward_account(program_id, &config, reward_account_info, signer, None, system_program)?;
+    let mut reward_account = RewardAccount::try_from_account_data(&reward_account_info.data.borrow())?;
+    require_unpaused(&config, &reward_account)?;
+
+    // Points are only granted with the config's earn authority, the game backend, signing
+    let required_authority = config.params.earn_authority;
+    if !authority.is_signer || *authority.key != required_authority {
+        prose!(config.params, "Earn must be signed by the earn authority {}", required_authority);
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    credit_earn(&config, reward_account_info, &mut reward_account, signer.key, points)
+}
+
+// Credit an authorized Earn to the reward account, subject to the cohort share,
+// cooldown, rate limit and recordable minimum, and save it
+fn credit_earn(
+    config: &Config,
+    reward_account_info: &AccountInfo,
+    reward_account: &mut RewardAccount,
+    user: &Pubkey,
+    points: u32,
+) -> ProgramResult {
+    // Each cohort is credited its own share of what the authority grants
+    let points = match config.params.cohort_count {
+        0 => points,
+        _ => {
+            let bps = config.params.cohort_earn_bps[reward_account.cohort as usize % MAX_COHORTS];
+            u32::try_from(points as u64 * bps as u64 / BPS_DENOMINATOR as u64)
+                .map_err(|_| ProgramError::ArithmeticOverflow)?
+        }
+    };
+
+    // Throttle how often a single account can earn
+    if config.params.earn_cooldown_secs != 0 {
+        let now = Clock::get()?.unix_timestamp;
+        let remaining = cooldown_remaining(reward_account.last_earn_ts, config.params.earn_cooldown_secs, now);
+        if remaining != 0 {
+            prose!(config.params, "Earn cooldown active until {}", now.saturating_add(remaining));
+            return Err(RewardError::Cooldown.into());
+        }
+        reward_account.last_earn_ts = now;
+    }
+
+    // Each earned point takes one token from a bucket that refills over time
+    if config.params.earn_bucket_capacity != 0 {
+        let now = Clock::get()?.unix_timestamp;
+        let tokens = earn_tokens_available(config, reward_account, now)?;
+        if points > tokens {
+            prose!(config.params, "Earn of {} points exceeds the {} left in the rate limit", points, tokens);
+            return Err(RewardError::RateLimited.into());
+        }
+        reward_account.earn_tokens = tokens - points;
+        reward_account.last_refill_ts = now;
+    }
+
+    // Earns below the recordable minimum wait in the pending bucket until it fills up
+    let pending = reward_account.pending_points.checked_add(points as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if pending < config.params.min_recordable_earn as u64 {
+        reward_account.pending_points = pending;
+        reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+        prose!(config.params, "Holding {} points as pending ({} of {} required)", points, pending, config.params.min_recordable_earn);
+        return Ok(());
+    }
+
+    // Add points to the total and handle overflow
+    reward_account.pending_points = 0;
+    reward_account.total_points = reward_account.total_points.checked_add(pending)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    reward_account.lifetime_points = reward_account.lifetime_points.checked_add(pending)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    // Save the updated reward account back to the account
+    reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+    emit_event(config, &RewardEvent::Earned {
+        user: *user,
+        points: pending,
+        new_total: reward_account.total_points,
+    })?;
+    prose!(config.params, "Earned {} points!", pending);
+    Ok(())
+}
+
+// Seconds left of a `cooldown` started at `last`, zero once it passed or before the first action
+fn cooldown_remaining(last: i64, cooldown: u32, now: i64) -> i64 {
+    if cooldown == 0 || last == 0 {
+        return 0;
+    }
+    last.saturating_add(cooldown as i64).saturating_sub(now).max(0)
+}
+
+// Points the account's earn bucket holds at `now`, refilled since the last limited Earn
+fn earn_tokens_available(config: &Config, reward_account: &RewardAccount, now: i64) -> Result<u32, ProgramError> {
+    let capacity = config.params.earn_bucket_capacity;
+    match reward_account.last_refill_ts {
+        0 => Ok(capacity),
+        last => {
+            let elapsed = now.saturating_sub(last).max(0) as u64;
+            let refill = elapsed.saturating_mul(config.params.earn_refill_per_sec as u64);
+            u32::try_from((reward_account.earn_tokens as u64).saturating_add(refill).min(capacity as u64))
+                .map_err(|_| ProgramError::ArithmeticOverflow)
+        }
+    }
+}
+
+// The token accounts read by instructions paying out of the vault into the user's token account
+struct TokenAccounts<'a, 'b> {
+    user_token_account: &'a AccountInfo<'b>,
+    vault_token_account: &'a AccountInfo<'b>,
+    mint_account: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+}
+
+// User token, vault, mint and token program of a payout
+fn next_token_accounts<'a, 'b>(
+    accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<TokenAccounts<'a, 'b>, ProgramError> {
+    Ok(TokenAccounts {
+        user_token_account: next_named_account(accounts_iter, "user token")?,
+        vault_token_account: next_named_account(accounts_iter, "vault token")?,
+        mint_account: next_named_account(accounts_iter, "mint")?,
+        token_program: next_token_program(accounts_iter)?,
+    })
+}
+
+// The token program, checked here so no CPI can hand the vault to an impostor
+fn next_token_program<'a, 'b>(
+    accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    let token_program = next_named_account(accounts_iter, "token program")?;
+    if !spl_token::check_id(token_program.key) {
+        msg!("Token program must be {}, got {}", spl_token::id(), token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(token_program)
+}
+
+// `owner`'s reward PDA, with its bump
+fn next_reward_account<'a, 'b>(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    accounts_iter: &mut core::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<(&'a AccountInfo<'b>, u8), ProgramError> {
+    let reward_account_info = next_named_account(accounts_iter, "reward")?;
+    let Some(reward_bump) = reward_address_bump(program_id, owner, reward_account_info) else {
+        msg!("Invalid reward account PDA");
+        return Err(RewardError::InvalidPda.into());
+    };
+    Ok((reward_account_info, reward_bump))
+}
+
+// Next account in the instruction's list, naming it in the log when the client left it out
+fn next_named_account<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    accounts_iter: &mut I,
+    name: &str,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    accounts_iter.next().ok_or_else(|| missing_account(name))
+}
+
+// Error for an account the instruction needs but the client did not pass
+fn missing_account(name: &str) -> ProgramError {
+    msg!("Missing {} account", name);
+    RewardError::MissingAccount.into()
+}
+
+// Whether the vault meets min_vault_balance, always true unless require_funded_vault is set.
+// Only the reward mint's vault PDA counts, so any other well-funded account cannot stand in for it
+fn vault_funded(program_id: &Pubkey, config: &Config, vault_token_account: &AccountInfo) -> Result<bool, ProgramError> {
+    if !config.params.require_funded_vault {
+        return Ok(true);
+    }
+    if *vault_token_account.key != find_vault_address(program_id, &config.mint).0 {
+        prose!(config.params, "Vault must be the vault PDA of the reward mint {}", config.mint);
+        return Err(RewardError::InvalidVault.into());
+    }
+    if *vault_token_account.owner != spl_token::id() {
+        prose!(config.params, "Vault is not a token account");
+        return Err(RewardError::InvalidVault.into());
+    }
+    let vault = TokenAccount::unpack(&vault_token_account.data.borrow())?;
+    if vault.mint != config.mint || vault.owner != vault_authority_address(program_id, config)? {
+        prose!(config.params, "Vault must hold the reward mint and be owned by the vault authority PDA");
+        return Err(RewardError::InvalidVault.into());
+    }
+    if vault.amount < config.params.min_vault_balance {
+        prose!(config.params, "Vault holds {} tokens, {} required", vault.amount, config.params.min_vault_balance);
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+// Run the sibling instruction checks a claim is configured for
+fn verify_claim_siblings(config: &Config, user: &Pubkey, instructions_sysvar: &AccountInfo) -> ProgramResult {
+    if config.params.require_kyc {
+        verify_kyc_attestation(config, user, instructions_sysvar)?;
+    }
+    if config.params.require_memo {
+        verify_memo(config, instructions_sysvar)?;
+    }
+    Ok(())
+}
+
+// Require an SPL Memo instruction anywhere before the current one
+fn verify_memo(config: &Config, instructions_sysvar: &AccountInfo) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in 0..current_index {
+        if load_instruction_at_checked(index as usize, instructions_sysvar)?.program_id == MEMO_PROGRAM_ID {
+            return Ok(());
+        }
+    }
+    prose!(config.params, "Claim requires a memo");
+    Err(RewardError::MemoRequired.into())
+}
+
+// Require an earlier instruction of the transaction to be an ed25519 signature
+// check of the user's KYC attestation by the admin. It need not be the one right
+// before, which ClaimSigned keeps for the oracle's signature
+fn verify_kyc_attestation(config: &Config, user: &Pubkey, instructions_sysvar: &AccountInfo) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in (0..current_index).rev() {
+        let attested = ed25519_signature_at(index, instructions_sysvar)?.is_some_and(|signed| {
+            signed.public_key == config.admin.as_ref() && signed.message == kyc_attestation_message(user)
+        });
+        if attested {
+            return Ok(());
+        }
+    }
+    prose!(config.params, "Claim requires a valid KYC attestation for {}", user);
+    Err(RewardError::KycRequired.into())
+}
+
+// A single signature checked by the ed25519 program
+struct Ed25519Signature {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+    message: Vec<u8>,
+}
+
+// The signature in the ed25519 instruction right before the current one, see ed25519_signature_at
+fn preceding_ed25519_signature(instructions_sysvar: &AccountInfo) -> Result<Option<Ed25519Signature>, ProgramError> {
+    match load_current_index_checked(instructions_sysvar)?.checked_sub(1) {
+        Some(index) => ed25519_signature_at(index, instructions_sysvar),
+        None => Ok(None),
+    }
+}
+
+// The signature in the transaction's instruction at `index` if it is an ed25519 check
+// holding exactly one with all data in that instruction. The runtime has already
+// verified it; callers only check what was signed and by whom.
+fn ed25519_signature_at(index: u16, instructions_sysvar: &AccountInfo) -> Result<Option<Ed25519Signature>, ProgramError> {
+    let instruction = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+    if instruction.program_id != ed25519_program::id() {
+        return Ok(None);
+    }
+
+    // Offsets are 7 x u16 after a 2-byte header; u16::MAX points into the same instruction
+    let data = &instruction.data;
+    let read_u16 = |at: usize| data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let offsets: Option<Vec<u16>> = (0..7).map(|i| read_u16(2 + 2 * i)).collect();
+    let Some(offsets) = offsets else {
+        return Ok(None);
+    };
+    let same_instruction = [offsets[1], offsets[3], offsets[6]].iter().all(|&index| index == u16::MAX);
+    if data.first() != Some(&1) || !same_instruction {
+        return Ok(None);
+    }
+    let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len).map(<[u8]>::to_vec);
+    Ok(match (slice(offsets[2], 32), slice(offsets[0], 64), slice(offsets[4], offsets[5] as usize)) {
+        (Some(public_key), Some(signature), Some(message)) => Some(Ed25519Signature { public_key, signature, message }),
+        _ => None,
+    })
+}
+
+// Mint replenish_amount into the vault when it holds less than replenish_threshold,
+// unless that would push total_minted past max_supply
+fn replenish_vault<'a>(
+    program_id: &Pubkey,
+    config: &mut Config,
+    vault_token_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    mint_authority_info: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    if mint_authority_info.key != &mint_authority_address(program_id, config)? {
+        prose!(config.params, "Invalid mint authority PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    // Claims keep paying from what the vault already holds
+    if config.minting_paused {
+        prose!(config.params, "Minting is paused, not replenishing the vault");
+        return Ok(());
+    }
+
+    let vault = TokenAccount::unpack(&vault_token_account.data.borrow())?;
+    if vault.amount >= config.params.replenish_threshold {
+        return Ok(());
+    }
+    if !within_supply_cap(config, config.params.replenish_amount)? {
+        prose!(config.params, "Skipping vault replenishment, supply cap of {} reached", config.params.max_supply);
+        return Ok(());
+    }
+
+    let amount = config.params.replenish_amount;
+    mint_to_vault(program_id, config, vault_token_account, mint_account, mint_authority_info, token_program, amount)?;
+    prose!(config.params, "Replenished vault with {} tokens", amount);
+    Ok(())
+}
+
+// Whether minting `amount` more keeps total_minted within max_supply. The mint's own
+// supply is not used, since burning tokens would free room under the cap
+fn within_supply_cap(config: &Config, amount: u64) -> Result<bool, ProgramError> {
+    let total = config.total_minted.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(config.params.max_supply == 0 || total <= config.params.max_supply)
+}
+
+// Mint `amount` into the vault as the mint authority PDA, counting it in total_minted
+fn mint_to_vault<'a>(
+    program_id: &Pubkey,
+    config: &mut Config,
+    vault_token_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    mint_authority_info: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    if mint_authority_info.key != &mint_authority_address(program_id, config)? {
+        prose!(config.params, "Invalid mint authority PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    invoke_signed(
+        &mint_to(
+            token_program.key,
+            mint_account.key,
+            vault_token_account.key,
+            mint_authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            mint_account.clone(),
+            vault_token_account.clone(),
+            mint_authority_info.clone(),
+            token_program.clone(),
+        ],
+        &[&[MINT_AUTHORITY_SEED, &[config.mint_authority_bump]]],
+    )?;
+    config.total_minted = config.total_minted.checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    emit_event(config, &RewardEvent::Minted { amount })
+}
+
+// Largest amount a single claim may take: max_vault_fraction_bps of the vault balance
+fn vault_claim_cap(config: &Config, vault_token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    if config.params.max_vault_fraction_bps >= BPS_DENOMINATOR {
+        return Ok(u64::MAX);
+    }
+    let vault = TokenAccount::unpack(&vault_token_account.data.borrow())?;
+    Ok((vault.amount as u128 * config.params.max_vault_fraction_bps as u128 / BPS_DENOMINATOR as u128) as u64)
+}
+
+// Count an Init against the rent payer's max_accounts_per_creator, creating the
+// creator stats PDA on the payer's first Init. Keying the cap by who funds the
+// accounts keeps a relayer from sponsoring unlimited signups of fresh wallets
+fn record_account_creation<'a>(
+    program_id: &Pubkey,
+    config: &Config,
+    payer: &AccountInfo<'a>,
+    creator_stats_info: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (creator_stats_pda, creator_stats_bump) = find_creator_stats_address(program_id, payer.key);
+    if creator_stats_info.key != &creator_stats_pda {
+        prose!(config.params, "Invalid creator stats PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+
+    let mut stats = if creator_stats_info.data_is_empty() {
+        create_pda_account(
+            payer,
+            creator_stats_info,
+            CREATOR_STATS_SIZE,
+            program_id,
+            system_program,
+            &[CREATOR_STATS_SEED, payer.key.as_ref(), &[creator_stats_bump]],
+        )?;
+        CreatorStats { creator: *payer.key, accounts_created: 0 }
+    } else {
+        CreatorStats::try_from_account_data(&creator_stats_info.data.borrow())?
+    };
+
+    if stats.accounts_created >= config.params.max_accounts_per_creator {
+        prose!(config.params, "{} already paid for {} reward accounts", payer.key, stats.accounts_created);
+        return Err(RewardError::CreationCapReached.into());
+    }
+    stats.accounts_created += 1;
+    stats.write_account_data(&mut creator_stats_info.data.borrow_mut())?;
+    Ok(())
+}
+
+// Whether `creator` already created max_accounts_per_creator reward accounts, per
+// its creator stats PDA, which does not exist before its first Init
+fn creation_cap_reached(program_id: &Pubkey, config: &Config, creator: &Pubkey, creator_stats_info: &AccountInfo) -> Result<bool, ProgramError> {
+    if creator_stats_info.key != &find_creator_stats_address(program_id, creator).0 {
+        prose!(config.params, "Invalid creator stats PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    if creator_stats_info.data_is_empty() {
+        return Ok(false);
+    }
+    let stats = CreatorStats::try_from_account_data(&creator_stats_info.data.borrow())?;
+    Ok(stats.accounts_created >= config.params.max_accounts_per_creator)
+}
+
+// Consume `nonce` of a voucher or oracle claim in the signer's nonce registry, creating
+// the registry on first use. A nonce below next_nonce was already redeemed or skipped,
+// so each signed authorization is redeemable at most once
+fn consume_nonce<'a>(
+    program_id: &Pubkey,
+    config: &Config,
+    signer: &AccountInfo<'a>,
+    nonce_registry_info: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    nonce: u64,
+) -> ProgramResult {
+    let (registry_pda, registry_bump) = find_nonce_registry_address(program_id, signer.key);
+    if nonce_registry_info.key != &registry_pda {
+        prose!(config.params, "Invalid nonce registry PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+
+    let mut registry = if nonce_registry_info.data_is_empty() {
+        create_pda_account(
+            signer,
+            nonce_registry_info,
+            NONCE_REGISTRY_SIZE,
+            program_id,
+            system_program,
+            &[NONCES_SEED, signer.key.as_ref(), &[registry_bump]],
+        )?;
+        NonceRegistry { user: *signer.key, next_nonce: 0 }
+    } else {
+        NonceRegistry::try_from_account_data(&nonce_registry_info.data.borrow())?
+    };
+
+    if nonce < registry.next_nonce {
+        prose!(config.params, "Nonce {} already used; next nonce is {}", nonce, registry.next_nonce);
+        return Err(RewardError::VoucherAlreadyUsed.into());
+    }
+    registry.next_nonce = nonce.checked_add(1).ok_or(ProgramError::InvalidArgument)?;
+    registry.write_account_data(&mut nonce_registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+// Referrer registered for `code`, or the default key for an unknown code unless
+// the config rejects those
+fn resolve_referral(program_id: &Pubkey, config: &Config, code: &[u8; 8], referral_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    let (referral_pda, _referral_bump) = find_referral_address(program_id, code);
+    if referral_info.key != &referral_pda {
+        prose!(config.params, "Invalid referral code PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    if referral_info.owner != program_id || referral_info.data_is_empty() {
+        if config.params.reject_unknown_referrals {
+            prose!(config.params, "Unknown referral code");
+            return Err(RewardError::UnknownReferralCode.into());
+        }
+        return Ok(Pubkey::default());
+    }
+    Ok(ReferralCode::try_from_account_data(&referral_info.data.borrow())?.referrer)
+}
+
+// Token payout for spending `required_points` at the exchange rate in effect
+fn claim_amount(config: &Config, required_points: u32) -> Result<u64, ProgramError> {
+    (required_points as u64).checked_mul(exchange_rate(config)?)
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+// The pending exchange rate once its timelock has passed, the configured one before
+fn exchange_rate(config: &Config) -> Result<u64, ProgramError> {
+    if config.pending_exchange_rate != 0 && Clock::get()?.unix_timestamp >= config.pending_rate_effective_ts {
+        return Ok(config.pending_exchange_rate);
+    }
+    Ok(config.params.exchange_rate)
+}
+
+// Fold a pending exchange rate that has taken effect into the config params
+fn settle_exchange_rate(config: &mut Config) -> ProgramResult {
+    if config.pending_exchange_rate != 0 && Clock::get()?.unix_timestamp >= config.pending_rate_effective_ts {
+        config.params.exchange_rate = config.pending_exchange_rate;
+        config.pending_exchange_rate = 0;
+        config.pending_rate_effective_ts = 0;
+    }
+    Ok(())
+}
+
+// Vault balance above vault_reserve, the most claims may take out of it
+fn vault_reserve_headroom(config: &Config, vault_token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    if config.params.vault_reserve == 0 {
+        return Ok(u64::MAX);
+    }
+    let vault = TokenAccount::unpack(&vault_token_account.data.borrow())?;
+    Ok(vault.amount.saturating_sub(config.params.vault_reserve))
+}
+
+// Check that both token accounts of a payout hold the reward mint and that the
+// receiving account belongs to `owner`
+fn validate_payout_accounts(
+    config: &Config,
+    owner: &Pubkey,
+    user_token_account: &AccountInfo,
+    vault_token_account: &AccountInfo,
+) -> ProgramResult {
+    if *user_token_account.owner != spl_token::id() || *vault_token_account.owner != spl_token::id() {
+        prose!(config.params, "Payout accounts must be token accounts");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let user_token = TokenAccount::unpack(&user_token_account.data.borrow())?;
+    let vault = TokenAccount::unpack(&vault_token_account.data.borrow())?;
+    if user_token.mint != config.mint || vault.mint != config.mint {
+        prose!(config.params, "Token accounts must hold the reward mint {}", config.mint);
+        return Err(RewardError::WrongMint.into());
+    }
+    if user_token.owner != *owner {
+        prose!(config.params, "User token account is not owned by {}", owner);
+        return Err(RewardError::TokenOwnerMismatch.into());
+    }
+    Ok(())
+}
+
+// Decimals of the reward mint, so payout events can be displayed without another lookup
+fn mint_decimals(config: &Config, mint_account: &AccountInfo) -> Result<u8, ProgramError> {
+    if *mint_account.key != config.mint {
+        prose!(config.params, "Mint account must be the reward mint {}", config.mint);
+        return Err(RewardError::WrongMint.into());
+    }
+    Ok(Mint::unpack(&mint_account.data.borrow())?.decimals)
+}
+
+// Spend the points for a claim plus the configured point fee, burning the fee
+fn debit_claim(reward_account: &mut RewardAccount, config: &mut Config, required_points: u32) -> ProgramResult {
+    throttle_claim(reward_account, config)?;
+    let fee = config.params.claim_point_fee;
+    let total_cost = required_points as u64 + fee as u64;
+    let shortfall = total_cost.saturating_sub(reward_account.total_points);
+    if shortfall > 0 {
+        if !config.params.allow_stake_draw {
+            prose!(config.params, "Not enough points to claim reward! {} required including a {} point fee", total_cost, fee);
+            return Err(RewardError::NotEnoughPoints.into());
+        }
+
+        // The portion drawn from the stake pays the penalty on top, rounded up
+        let penalty = (shortfall * config.params.stake_draw_penalty_bps as u64)
+            .div_ceil(BPS_DENOMINATOR as u64);
+        let drawn = shortfall.checked_add(penalty)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if reward_account.staked_points < drawn {
+            prose!(config.params, "Not enough points to claim reward! {} more required from the stake, including a {} point penalty", drawn, penalty);
+            return Err(RewardError::NotEnoughPoints.into());
+        }
+        reward_account.staked_points = reward_account.staked_points.checked_sub(drawn)
+            .ok_or(RewardError::NotEnoughPoints)?;
+        config.total_points_destroyed = config.total_points_destroyed.checked_add(penalty)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    reward_account.total_points = reward_account.total_points.checked_sub(total_cost - shortfall)
+        .ok_or(RewardError::NotEnoughPoints)?;
+    reward_account.rewards_claimed = reward_account.rewards_claimed.checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    config.total_points_destroyed = config.total_points_destroyed.checked_add(fee as u64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Reject a claim within claim_cooldown_secs of the last one, or a second claim in
+// the epoch under one_claim_per_epoch, and remember this one
+fn throttle_claim(reward_account: &mut RewardAccount, config: &Config) -> ProgramResult {
+    let cooldown = config.params.claim_cooldown_secs;
+    if cooldown == 0 && !config.params.one_claim_per_epoch {
+        return Ok(());
+    }
+    let clock = Clock::get()?;
+    if cooldown != 0 {
+        let remaining = cooldown_remaining(reward_account.last_claim_ts, config.params.claim_cooldown_secs, clock.unix_timestamp);
+        if remaining != 0 {
+            prose!(config.params, "Claim cooldown active until {}", clock.unix_timestamp.saturating_add(remaining));
+            return Err(RewardError::ClaimCooldown.into());
+        }
+        reward_account.last_claim_ts = clock.unix_timestamp;
+    }
+    if config.params.one_claim_per_epoch {
+        // Tracking the epoch after the claim keeps a claim in epoch 0 apart from no claim at all
+        if clock.epoch < reward_account.next_claim_epoch {
+            prose!(config.params, "Already claimed in epoch {}", clock.epoch);
+            return Err(RewardError::AlreadyClaimedThisEpoch.into());
+        }
+        reward_account.next_claim_epoch = clock.epoch.saturating_add(1);
+    }
+    Ok(())
+}
+
+// Upgrade a reward account of an older layout in place to REWARD_ACCOUNT_VERSION,
+// with the signer topping up rent. Returns false if it is already current.
+// The 8-byte layout of the old lib.rs has no mint, so it is only upgraded when
+// the mint account is passed
+fn migrate_reward_account<'a>(
+    program_id: &Pubkey,
+    config: &Config,
+    reward_account_info: &AccountInfo<'a>,
+    signer: &AccountInfo<'a>,
+    mint_account: Option<&AccountInfo<'a>>,
+    system_program: Option<&AccountInfo<'a>>,
+) -> Result<bool, ProgramError> {
+    if reward_account_info.owner != program_id {
+        prose!(config.params, "Reward account is not owned by the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Current accounts carry a discriminator and version; older layouts are told apart by size
+    let mut reward_account = {
+        let data = reward_account_info.data.borrow();
+        match data.len() {
+            REWARD_ACCOUNT_SIZE if data[..DISCRIMINATOR_LEN] != RewardAccount::DISCRIMINATOR => {
+                prose!(config.params, "Account is not a reward account");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            REWARD_ACCOUNT_SIZE if data[DISCRIMINATOR_LEN] == REWARD_ACCOUNT_VERSION => return Ok(false),
+            REWARD_ACCOUNT_SIZE => {
+                prose!(config.params, "Unknown reward account version {}", data[DISCRIMINATOR_LEN]);
+                return Err(ProgramError::InvalidAccountData);
+            }
+            _ => read_legacy_reward_account(config, &data, signer.key, mint_account)?,
+        }
+    };
+
+    // Older layouts did not store the bump, so it is searched for this one time
+    reward_account.bump = find_reward_address(program_id, signer.key).1;
+
+    // The signer tops up rent for the larger account
+    let rent = Rent::get()?;
+    let shortfall = rent.minimum_balance(REWARD_ACCOUNT_SIZE)
+        .saturating_sub(reward_account_info.lamports());
+    if shortfall > 0 {
+        let system_program = system_program.ok_or_else(|| missing_account("system program"))?;
+        invoke(
+            &system_instruction::transfer(signer.key, reward_account_info.key, shortfall),
+            &[signer.clone(), reward_account_info.clone(), system_program.clone()],
+        )?;
+    }
+    reward_account_info.resize(REWARD_ACCOUNT_SIZE)?;
+    reward_account.write_account_data(&mut reward_account_info.data.borrow_mut())?;
+    Ok(true)
+}
+
+// Carry a reward account of the first release (with a mint) or of the old lib.rs
+// (without one) over to the current layout, owned by `owner`
+fn read_legacy_reward_account(
+    config: &Config,
+    data: &[u8],
+    owner: &Pubkey,
+    mint_account: Option<&AccountInfo>,
+) -> Result<RewardAccount, ProgramError> {
+    let (total_points, rewards_claimed, mint) = match data.len() {
+        BASE_REWARD_ACCOUNT_SIZE => {
+            let base = BaseRewardAccount::try_from_slice(data)?;
+            (base.total_points, base.rewards_claimed, base.mint)
+        }
+        LEGACY_REWARD_ACCOUNT_SIZE => {
+            let mint_account = mint_account.ok_or_else(|| missing_account("mint"))?;
+            if *mint_account.key != config.mint {
+                prose!(config.params, "Mint does not match the reward mint {}", config.mint);
+                return Err(RewardError::WrongMint.into());
+            }
+            let total_points = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let rewards_claimed = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+            (total_points, rewards_claimed, *mint_account.key)
+        }
+        len => {
+            prose!(config.params, "Unexpected reward account size {}", len);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    };
+    Ok(RewardAccount {
+        total_points: total_points.into(),
+        rewards_claimed,
+        mint,
+        lifetime_points: total_points.into(), // Earlier spending was never recorded
+        authority: config.params.earn_authority,
+        owner: *owner,
+        ..RewardAccount::default()
+    })
+}
+
+// Validate a scheduled claim PDA and deserialize it
+fn load_scheduled_claim(program_id: &Pubkey, config: &Config, scheduled_info: &AccountInfo) -> Result<ScheduledClaim, ProgramError> {
+    if scheduled_info.owner != program_id || scheduled_info.data_len() != SCHEDULED_CLAIM_SIZE {
+        prose!(config.params, "Invalid scheduled claim account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let scheduled = ScheduledClaim::try_from_account_data(&scheduled_info.data.borrow())?;
+    let (scheduled_pda, _scheduled_bump) = find_scheduled_claim_address(program_id, &scheduled.user, scheduled.id);
+    if scheduled_info.key != &scheduled_pda {
+        prose!(config.params, "Invalid scheduled claim PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    Ok(scheduled)
+}
+
+// Validate a stream PDA and deserialize it
+fn load_stream(program_id: &Pubkey, config: &Config, stream_info: &AccountInfo) -> Result<RewardStream, ProgramError> {
+    if stream_info.owner != program_id || stream_info.data_len() != REWARD_STREAM_SIZE {
+        prose!(config.params, "Invalid stream account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let stream = RewardStream::try_from_account_data(&stream_info.data.borrow())?;
+    let (stream_pda, _stream_bump) = find_stream_address(program_id, &stream.user, stream.id);
+    if stream_info.key != &stream_pda {
+        prose!(config.params, "Invalid stream PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    Ok(stream)
+}
+
+// Share of a stream's total_amount accrued by now, linear in the elapsed seconds
+fn streamed_amount(stream: &RewardStream) -> Result<u64, ProgramError> {
+    let elapsed = Clock::get()?.unix_timestamp.saturating_sub(stream.start_ts).clamp(0, stream.duration as i64);
+    Ok((stream.total_amount as u128 * elapsed as u128 / stream.duration as u128) as u64)
+}
+
+// Transfer `amount` from the vault through the vault authority PDA, within the vault cap and reserve
+fn pay_from_vault<'a>(
+    program_id: &Pubkey,
+    config: &Config,
+    token_program: &AccountInfo<'a>,
+    vault_token_account: &AccountInfo<'a>,
+    user_token_account: &AccountInfo<'a>,
+    vault_authority_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let cap = vault_claim_cap(config, vault_token_account)?;
+    if amount > cap {
+        prose!(config.params, "Claim of {} exceeds the vault cap of {}", amount, cap);
+        return Err(RewardError::ExceedsVaultFractionCap.into());
+    }
+    if amount > vault_reserve_headroom(config, vault_token_account)? {
+        prose!(config.params, "Claim of {} would dip into the vault reserve of {}", amount, config.params.vault_reserve);
+        return Err(RewardError::VaultReserveProtected.into());
+    }
+
+    let vault_authority_pda = vault_authority_address(program_id, config)?;
+    if vault_authority_info.key != &vault_authority_pda {
+        prose!(config.params, "Invalid vault authority PDA");
+        return Err(RewardError::InvalidVault.into());
+    }
+    if TokenAccount::unpack(&vault_token_account.data.borrow())?.owner != vault_authority_pda {
+        prose!(config.params, "Vault is not held by the vault authority PDA");
+        return Err(RewardError::InvalidVault.into());
+    }
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            vault_token_account.key,
+            user_token_account.key,
+            vault_authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vault_token_account.clone(),
+            user_token_account.clone(),
+            vault_authority_info.clone(),
+            token_program.clone(),
+        ],
+        &[&[VAULT_AUTHORITY_SEED, &[config.vault_authority_bump]]],
+    )
+}
+
+// Create the PDA `account` signed for by `seeds` with `space` bytes owned by `owner`,
+// the payer covering rent. CreateAccount fails on an address holding lamports, so one
+// someone pre-funded is topped up, allocated and assigned instead
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(payer.key, account.key, rent, space as u64, owner),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[seeds],
+        );
+    }
+
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+// Move all lamports of a program account to `destination` and hand the emptied
+// account back to the system program. Returns the lamports moved.
+fn close_account(account: &AccountInfo, destination: &AccountInfo) -> Result<u64, ProgramError> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination.lamports().checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.data.borrow_mut().fill(0);
+    account.resize(0)?;
+    account.assign(&system_program::id());
+    Ok(lamports)
+}
+
+// Validate the config PDA passed by the client and deserialize it
+fn load_config(program_id: &Pubkey, config_info: &AccountInfo) -> Result<Config, ProgramError> {
+    if config_info.owner != program_id {
+        msg!("Invalid config account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // Only the program writes accounts it owns, so the stored bump can be trusted to rebuild the address
+    let config = Config::try_from_account_data(&config_info.data.borrow())?;
+    if Pubkey::create_program_address(&[CONFIG_SEED, &[config.bump]], program_id) != Ok(*config_info.key) {
+        prose!(config.params, "Invalid config account PDA");
+        return Err(RewardError::InvalidPda.into());
+    }
+    Ok(config)
+}
+
+// Vault authority PDA, rebuilt from the bump InitConfig stored
+fn vault_authority_address(program_id: &Pubkey, config: &Config) -> Result<Pubkey, ProgramError> {
+    Ok(Pubkey::create_program_address(&[VAULT_AUTHORITY_SEED, &[config.vault_authority_bump]], program_id)?)
+}
+
+// Mint authority PDA, rebuilt from the bump InitConfig stored
+fn mint_authority_address(program_id: &Pubkey, config: &Config) -> Result<Pubkey, ProgramError> {
+    Ok(Pubkey::create_program_address(&[MINT_AUTHORITY_SEED, &[config.mint_authority_bump]], program_id)?)
+}
+
+// Bump of `owner`'s reward PDA if `reward_account_info` is that PDA. Current accounts
+// carry their bump, so only older layouts and accounts not created yet are searched for
+fn reward_address_bump(program_id: &Pubkey, owner: &Pubkey, reward_account_info: &AccountInfo) -> Option<u8> {
+    let data = reward_account_info.data.borrow();
+    if reward_account_info.owner == program_id && data.len() == REWARD_ACCOUNT_SIZE && data[DISCRIMINATOR_LEN] == REWARD_ACCOUNT_VERSION {
+        let bump = RewardAccount::try_from_account_data(&data).ok()?.bump;
+        let address = Pubkey::create_program_address(&[REWARD_SEED, owner.as_ref(), &[bump]], program_id).ok()?;
+        return (address == *reward_account_info.key).then_some(bump);
+    }
+    let (address, bump) = find_reward_address(program_id, owner);
+    (address == *reward_account_info.key).then_some(bump)
+}
+
+// Log an event once: as a BINARY_EVENT_PREFIX line when binary_events is enabled,
+// as Borsh-encoded program data otherwise
+fn emit_event(config: &Config, event: &RewardEvent) -> ProgramResult {
+    if config.params.binary_events {
+        msg!("{}", binary_event_line(event)?);
+    } else {
+        sol_log_data(&[&borsh::to_vec(event)?]);
+    }
+    Ok(())
+}
+
+// Whether the config PDA passed among `accounts` has binary_events set, for logs written
+// before any instruction loads it. Only the account at the config address is read, and
+// only once the program owns it
+fn binary_events_enabled(program_id: &Pubkey, accounts: &[AccountInfo]) -> bool {
+    let (config_pda, _) = find_config_address(program_id);
+    accounts.iter()
+        .find(|info| *info.key == config_pda && info.owner == program_id)
+        .and_then(|info| Config::try_from_account_data(&info.data.borrow()).ok())
+        .is_some_and(|config| config.params.binary_events)
+}
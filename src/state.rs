@@ -0,0 +1,380 @@
+// Account layouts stored by the program
+
+use borsh::{BorshDeserialize, BorshSerialize};        // For (de)serialization of data structures
+use solana_keccak_hasher::hashv;                      // Keccak256 for the balance Merkle tree
+use solana_program::{
+    entrypoint::ProgramResult,                        // Return type for program functions
+    msg,                                              // Macro for logging messages
+    program_error::ProgramError,                      // Error types for the program
+    pubkey::Pubkey,                                   // Public key type
+};
+
+// Length of the discriminator every account the program stores starts with
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+// Account types the program stores. Their data starts with a discriminator, the first
+// 8 bytes of sha256("account:<type name>") as Anchor derives it, so an account of one
+// type can never be read as another
+pub trait AccountState: BorshSerialize + BorshDeserialize {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN];
+
+    // Deserialize account data, rejecting data written for any other type
+    fn try_from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.split_first_chunk::<DISCRIMINATOR_LEN>() {
+            Some((discriminator, rest)) if *discriminator == Self::DISCRIMINATOR => Ok(Self::try_from_slice(rest)?),
+            _ => {
+                msg!("Account discriminator does not match the expected account type");
+                Err(ProgramError::InvalidAccountData)
+            }
+        }
+    }
+
+    // Write the discriminator followed by the serialized fields
+    fn write_account_data(&self, data: &mut [u8]) -> ProgramResult {
+        if data.len() < DISCRIMINATOR_LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (discriminator, rest) = data.split_at_mut(DISCRIMINATOR_LEN);
+        discriminator.copy_from_slice(&Self::DISCRIMINATOR);
+        self.serialize(&mut &mut rest[..])?;
+        Ok(())
+    }
+}
+
+// Struct to store reward account data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RewardAccount {
+    pub version: u8,              // Layout version, REWARD_ACCOUNT_VERSION for accounts written now
+    pub total_points: u64,        // Total reward points accumulated
+    pub rewards_claimed: u32,     // Number of rewards claimed
+    pub mint: Pubkey,             // Mint address of "WAGUS" token
+    pub staked_points: u64,       // Points restaked through ClaimAndStake
+    pub pending_points: u64,      // Earns held back until they reach min_recordable_earn
+    pub lifetime_points: u64,     // All points ever credited, never reduced by spending
+    pub dust_credit: u64,         // Restaked token base units still short of a whole point
+    pub next_schedule_id: u32,    // Id of the next ScheduleClaim or OpenStream, used in its PDA seeds
+    pub authority: Pubkey,        // The config's earn authority at Init, kept for the record; SetPaused checks the current one
+    pub scheduled_claims: u32,    // Scheduled claims and streams not yet paid out in full or cancelled
+    pub last_earn_ts: i64,        // Unix time of the last accepted Earn, for earn_cooldown_secs
+    pub paused: bool,             // Set by the earn authority or the admin to halt earning and claiming
+    pub note: [u8; REWARD_NOTE_LEN], // Opaque bytes set by the owner through SetNote, e.g. an encrypted reference
+    pub init_key: [u8; 32],       // Idempotency key Init was called with, zero if none
+    pub earn_tokens: u32,         // Points left in the earn rate-limit bucket
+    pub last_refill_ts: i64,      // Unix time earn_tokens was last refilled, zero before the first limited Earn
+    pub referrer: Pubkey,         // Referrer resolved from the code passed to Init, default if none
+    pub cohort: u8,               // A/B cohort chosen at Init, selecting the cohort_earn_bps entry
+    pub next_claim_epoch: u64,    // First epoch the next claim may land in under one_claim_per_epoch
+    pub bump: u8,                 // Canonical bump of the account's PDA, so instructions skip find_program_address
+    pub last_claim_ts: i64,       // Unix time of the last claim under claim_cooldown_secs, zero before the first
+    pub owner: Pubkey,            // Wallet the account belongs to, the key in its PDA seeds
+}
+
+impl Default for RewardAccount {
+    fn default() -> Self {
+        Self {
+            version: REWARD_ACCOUNT_VERSION,
+            total_points: 0,
+            rewards_claimed: 0,
+            mint: Pubkey::default(),
+            staked_points: 0,
+            pending_points: 0,
+            lifetime_points: 0,
+            dust_credit: 0,
+            next_schedule_id: 0,
+            authority: Pubkey::default(),
+            scheduled_claims: 0,
+            last_earn_ts: 0,
+            paused: false,
+            note: [0; REWARD_NOTE_LEN],
+            init_key: [0; 32],
+            earn_tokens: 0,
+            last_refill_ts: 0,
+            referrer: Pubkey::default(),
+            cohort: 0,
+            next_claim_epoch: 0,
+            bump: 0,
+            last_claim_ts: 0,
+            owner: Pubkey::default(),
+        }
+    }
+}
+
+impl AccountState for RewardAccount {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [225, 81, 31, 253, 84, 234, 171, 129];
+}
+
+// Current RewardAccount layout version; bump it whenever a field is added
+pub const REWARD_ACCOUNT_VERSION: u8 = 1;
+
+// Length of the owner's note in bytes
+pub const REWARD_NOTE_LEN: usize = 64;
+
+// Size of a RewardAccount account in bytes, discriminator included
+pub const REWARD_ACCOUNT_SIZE: usize = DISCRIMINATOR_LEN + 1 + 8 + 4 + 32 + 8 + 8 + 8 + 8 + 4 + 32 + 4 + 8 + 1 + REWARD_NOTE_LEN + 32 + 4 + 8 + 32 + 1 + 8 + 1 + 8 + 32;
+
+// RewardAccount as the first release stored it, without a version byte or
+// discriminator. Migration carries it over to the current layout
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct BaseRewardAccount {
+    pub total_points: u32,
+    pub rewards_claimed: u32,
+    pub mint: Pubkey,
+}
+
+// Size of a BaseRewardAccount in bytes
+pub const BASE_REWARD_ACCOUNT_SIZE: usize = 4 + 4 + 32;
+
+// Size of the original RewardAccount layout of the old lib.rs: total_points and
+// rewards_claimed only, the mint being filled in from the config on migration
+pub const LEGACY_REWARD_ACCOUNT_SIZE: usize = 4 + 4;
+
+// Claim paid out no earlier than execute_after_ts, stored in the PDA derived
+// from [b"scheduled_claim", user, id as u32 LE]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct ScheduledClaim {
+    pub user: Pubkey,             // Wallet whose points were debited
+    pub id: u32,                  // Per-user sequence number from next_schedule_id
+    pub destination: Pubkey,      // Token account receiving the payout
+    pub required_points: u64,     // Points refunded if the claim is cancelled
+    pub amount: u64,              // Token base units paid out on execution
+    pub execute_after_ts: i64,    // Unix time from which anyone may execute it
+}
+
+impl AccountState for ScheduledClaim {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [32, 112, 191, 63, 45, 184, 243, 16];
+}
+
+// Size of a ScheduledClaim account in bytes, discriminator included
+pub const SCHEDULED_CLAIM_SIZE: usize = DISCRIMINATOR_LEN + 32 + 4 + 32 + 8 + 8 + 8;
+
+// Payout that accrues every second from start_ts over duration, stored in the
+// PDA derived from [b"stream", user, id as u32 LE]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct RewardStream {
+    pub user: Pubkey,             // Wallet whose points were debited
+    pub id: u32,                  // Per-user sequence number from next_schedule_id
+    pub destination: Pubkey,      // Token account receiving withdrawals
+    pub required_points: u64,     // Points spent; the unstreamed share is refunded on cancel
+    pub total_amount: u64,        // Token base units streamed over the whole duration
+    pub withdrawn: u64,           // Token base units already paid out
+    pub start_ts: i64,            // Unix time the stream opened
+    pub duration: u32,            // Seconds until total_amount has fully accrued
+}
+
+impl AccountState for RewardStream {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [92, 237, 114, 174, 9, 138, 200, 77];
+}
+
+// Size of a RewardStream account in bytes, discriminator included
+pub const REWARD_STREAM_SIZE: usize = DISCRIMINATOR_LEN + 32 + 4 + 32 + 8 + 8 + 8 + 8 + 4;
+
+// Maximum number of recovery guardians per user
+pub const MAX_GUARDIANS: usize = 5;
+
+// Wallets that can jointly move a user's points to a new owner, stored in the
+// PDA derived from [b"guardians", user]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct GuardianSet {
+    pub user: Pubkey,             // Wallet whose reward account the guardians protect
+    pub threshold: u8,            // Distinct guardian signatures RecoverOwnership needs
+    pub guardian_count: u8,       // Entries of `guardians` in use
+    pub guardians: [Pubkey; MAX_GUARDIANS], // Guardian wallets, unused entries zeroed
+}
+
+impl AccountState for GuardianSet {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [120, 77, 74, 98, 34, 83, 96, 125];
+}
+
+// Size of a GuardianSet account in bytes, discriminator included
+pub const GUARDIAN_SET_SIZE: usize = DISCRIMINATOR_LEN + 32 + 1 + 1 + 32 * MAX_GUARDIANS;
+
+// Reward accounts a wallet has paid the rent of with Init, stored in the PDA
+// derived from [b"creator_stats", creator]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct CreatorStats {
+    pub creator: Pubkey,          // Wallet that paid for the Inits, the signer or a sponsoring relayer
+    pub accounts_created: u32,    // Reward accounts created so far, including closed ones
+}
+
+impl AccountState for CreatorStats {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [239, 158, 112, 237, 227, 82, 97, 129];
+}
+
+// Size of a CreatorStats account in bytes, discriminator included
+pub const CREATOR_STATS_SIZE: usize = DISCRIMINATOR_LEN + 32 + 4;
+
+// Next voucher or oracle claim nonce a user may redeem, stored in the PDA derived
+// from [b"nonces", user]. Nonces only move forward, so redeeming one also retires
+// every unredeemed one with a lower nonce
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct NonceRegistry {
+    pub user: Pubkey,             // Wallet the vouchers and oracle claims were issued to
+    pub next_nonce: u64,          // Lowest nonce still redeemable
+}
+
+impl AccountState for NonceRegistry {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [115, 114, 189, 172, 239, 92, 79, 240];
+}
+
+// Size of a NonceRegistry account in bytes, discriminator included
+pub const NONCE_REGISTRY_SIZE: usize = DISCRIMINATOR_LEN + 32 + 8;
+
+// Referrer a campaign code credits, stored by the admin in the PDA derived
+// from [b"referral", code]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct ReferralCode {
+    pub code: [u8; 8],            // Code users pass to Init
+    pub referrer: Pubkey,         // Account credited for signups with the code
+}
+
+impl AccountState for ReferralCode {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [227, 239, 247, 224, 128, 187, 44, 229];
+}
+
+// Size of a ReferralCode account in bytes, discriminator included
+pub const REFERRAL_CODE_SIZE: usize = DISCRIMINATOR_LEN + 8 + 32;
+
+// Tunable parameters supplied by the admin when creating the config
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ConfigParams {
+    pub exchange_rate: u64,       // Token base units paid out per point
+    pub binary_events: bool,      // Log events as prefixed base64 instead of prose
+    pub min_recordable_earn: u32, // Smallest earn credited at once; smaller ones accumulate (0 disables)
+    pub claim_point_fee: u32,     // Extra points burned on every claim
+    pub require_funded_vault: bool, // Init rejects users until the vault holds min_vault_balance
+    pub min_vault_balance: u64,   // Token base units the vault must hold for Init
+    pub auto_replenish: bool,     // Claim mints into a vault that runs below replenish_threshold
+    pub replenish_threshold: u64, // Vault balance below which Claim replenishes it
+    pub replenish_amount: u64,    // Token base units minted per replenishment
+    pub max_supply: u64,          // Tokens MintToken and replenishment may mint in total, see total_minted (0 disables)
+    pub max_vault_fraction_bps: u16, // Largest claim as a share of the vault balance (10000 disables)
+    pub require_kyc: bool,        // Claim needs an admin-signed KYC attestation for the user
+    pub accumulate_dust: bool,    // ClaimAndStake keeps sub-point remainders instead of rejecting zero-point restakes
+    pub program_end_ts: i64,      // Unix time after which the config may be closed (0 = open-ended)
+    pub max_scheduled_claims: u32, // Outstanding scheduled claims allowed per user (0 disables)
+    pub vault_reserve: u64,       // Vault balance claims may never dip into (0 disables)
+    pub earn_cooldown_secs: u32,  // Seconds a user must wait between credited earns (0 disables)
+    pub require_memo: bool,       // Claims must come after an SPL Memo instruction in the same transaction
+    pub max_accounts_per_creator: u32, // Reward accounts a wallet may ever pay for with Init (0 disables)
+    pub oracle: Pubkey,           // Key whose ed25519 signatures authorize ClaimSigned payouts (default disables)
+    pub max_total_users: u32,     // Reward accounts Init may ever create across all wallets (0 disables)
+    pub earn_bucket_capacity: u32, // Most points an account can earn in a burst (0 disables rate limiting)
+    pub earn_refill_per_sec: u32, // Points added back to an account's earn bucket each second
+    pub allow_stake_draw: bool,   // Claims short on points may make up the difference from staked_points
+    pub stake_draw_penalty_bps: u16, // Extra staked points burned on top of the portion a claim draws
+    pub rate_change_timelock_secs: u32, // Shortest delay before a SetExchangeRate change takes effect
+    pub reject_unknown_referrals: bool, // Init fails on a referral code with no SetReferralCode entry instead of ignoring it
+    pub max_staked_per_user: u32, // Most staked points ClaimAndStake may leave on one account (0 disables)
+    pub heartbeat_timeout_secs: u32, // Admin silence after which anyone may pause the program (0 disables)
+    pub cohort_count: u8,         // Cohorts Init may assign, numbered from 0 (0 disables cohorts)
+    pub cohort_earn_bps: [u16; MAX_COHORTS], // Share of each Earn a cohort is credited, used while cohorts are enabled
+    pub admin_cancels_streams: bool, // CancelStream is signed by the admin instead of the stream's owner
+    pub one_claim_per_epoch: bool, // Each account may claim at most once per epoch
+    pub earn_authority: Pubkey,   // Backend key that must co-sign every Earn (required)
+    pub claim_cooldown_secs: u32, // Seconds a user must wait between claims (0 disables)
+}
+
+// Most cohorts a config can split users into
+pub const MAX_COHORTS: usize = 4;
+
+// Serialized size of ConfigParams in bytes
+pub const CONFIG_PARAMS_SIZE: usize = 8 + 1 + 4 + 4 + 1 + 8 + 1 + 8 + 8 + 8 + 2 + 1 + 1 + 8 + 4 + 8 + 4 + 1 + 4 + 32 + 4 + 4 + 4 + 1 + 2 + 4 + 1 + 4 + 4 + 1 + 2 * MAX_COHORTS + 1 + 1 + 32 + 4;
+
+// Basis points in 100%
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+// Program-wide configuration, stored in the PDA derived from b"config"
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct Config {
+    pub admin: Pubkey,            // Authority that initialized the config
+    pub mint: Pubkey,             // Reward token mint, fixed by InitConfig; Init, claims and InitVault only accept it
+    pub params: ConfigParams,     // Admin-supplied parameters
+    pub total_points_destroyed: u64, // Points removed from circulation by fees
+    pub balance_root: BalanceRoot, // Merkle commitment to all balances
+    pub archived: bool,           // Set once by Archive; every mutating instruction is rejected after it
+    pub minting_paused: bool,     // Set by SetMintingPaused; stops MintToken and vault replenishing, not claims
+    pub total_users: u32,         // Reward accounts created by Init, never decremented
+    pub pending_exchange_rate: u64, // Rate queued by SetExchangeRate (0 = none)
+    pub pending_rate_effective_ts: i64, // Unix time the pending rate replaces params.exchange_rate
+    pub last_heartbeat_ts: i64,   // Unix time of the admin's last Heartbeat, or of InitConfig
+    pub dead_man_paused: bool,    // Set by TriggerDeadManPause, cleared by the next Heartbeat
+    pub program_paused: bool,     // Set by SetProgramPaused; stops earning, minting and payouts until the admin clears it
+    pub pending_admin: Pubkey,    // Admin proposed by ProposeAdmin, taking over once it signs AcceptAdmin (default = none)
+    pub total_minted: u64,        // Tokens the program minted into vaults, by MintToken and replenishing
+    pub bump: u8,                 // Canonical bump of the config PDA
+    pub vault_authority_bump: u8, // Canonical bump of the vault authority PDA
+    pub mint_authority_bump: u8,  // Canonical bump of the mint authority PDA
+}
+
+impl AccountState for Config {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [155, 12, 170, 224, 30, 250, 204, 130];
+}
+
+// Size of a Config account in bytes, discriminator included
+pub const CONFIG_SIZE: usize = DISCRIMINATOR_LEN + 32 + 32 + CONFIG_PARAMS_SIZE + 8 + BALANCE_ROOT_SIZE + 1 + 1 + 4 + 8 + 8 + 8 + 1 + 1 + 32 + 8 + 1 + 1 + 1;
+
+// Depth of the balance Merkle tree, allowing up to 2^16 leaves
+pub const BALANCE_TREE_DEPTH: usize = 16;
+
+// Incremental Merkle tree over (reward account, points) leaves, built page by
+// page with ComputeBalanceRoot. Only the left frontier of the tree is kept;
+// empty leaves are all-zero hashes.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct BalanceRoot {
+    pub leaf_count: u32,          // Leaves folded into the current snapshot
+    pub branch: [[u8; 32]; BALANCE_TREE_DEPTH], // Left frontier of the tree
+    pub root: [u8; 32],           // Root of the last finalized snapshot
+    pub finalized: bool,          // Whether `root` reflects the leaves folded so far
+}
+
+// Serialized size of BalanceRoot in bytes
+pub const BALANCE_ROOT_SIZE: usize = 4 + 32 * BALANCE_TREE_DEPTH + 32 + 1;
+
+impl BalanceRoot {
+    // Append a leaf to the tree
+    pub(crate) fn fold(&mut self, leaf: [u8; 32]) -> ProgramResult {
+        if self.leaf_count as usize >= 1 << BALANCE_TREE_DEPTH {
+            msg!("Balance tree is full");
+            return Err(ProgramError::InvalidArgument);
+        }
+        self.leaf_count += 1;
+        let mut node = leaf;
+        let mut size = self.leaf_count;
+        for height in 0..BALANCE_TREE_DEPTH {
+            if size & 1 == 1 {
+                self.branch[height] = node;
+                break;
+            }
+            node = hash_pair(&self.branch[height], &node);
+            size >>= 1;
+        }
+        Ok(())
+    }
+
+    // Compute the root over the leaves folded so far
+    pub(crate) fn compute_root(&self) -> [u8; 32] {
+        let mut node = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.leaf_count;
+        for height in 0..BALANCE_TREE_DEPTH {
+            node = if size & 1 == 1 {
+                hash_pair(&self.branch[height], &node)
+            } else {
+                hash_pair(&node, &zero)
+            };
+            zero = hash_pair(&zero, &zero);
+            size >>= 1;
+        }
+        node
+    }
+}
+
+// Leaf committing to a wallet's balance: keccak(owner || points as u64 LE)
+pub fn balance_leaf(owner: &Pubkey, total_points: u64) -> [u8; 32] {
+    hashv(&[owner.as_ref(), &total_points.to_le_bytes()]).to_bytes()
+}
+
+// Interior node of the balance tree: keccak(left || right)
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[left, right]).to_bytes()
+}
@@ -30,19 +30,29 @@ try {
 // Constants and Public Keys
 const PROGRAM_ID = new PublicKey('2ga161fxHesc8YATYz2CconNkTSpCJVABrjbBKGtRYGF');
 const [rewardAccountPda, rewardBump] = await PublicKey.findProgramAddress(
-    [Buffer.from("reward")],
+    [Buffer.from("reward"), payer.publicKey.toBuffer()],
+    PROGRAM_ID
+);
+const [configPda] = await PublicKey.findProgramAddress(
+    [Buffer.from("config")],
+    PROGRAM_ID
+);
+const [vaultAuthorityPda] = await PublicKey.findProgramAddress(
+    [Buffer.from("vault_authority")],
     PROGRAM_ID
 );
 const ACCOUNTS = {
     reward: rewardAccountPda,
+    config: configPda,
+    vaultAuthority: vaultAuthorityPda,
     userToken: new PublicKey('6UR1TvXTocdnjCWewwq7LiZfR9gnp8wS4R94pSsYhwja'),
     vaultToken: new PublicKey('3Jz4UFKq6NBke45J2en3UD733xpHkAekmW8Cn5Tsx4uA'),
     mint: new PublicKey('Bqw2nob1NpDCnEBEtPqnUVoDqW97JRUK8js5VjyC5Q4n'),
     tokenProgram: TOKEN_PROGRAM_ID
 };
 
-// Function to send transactions
-async function sendTransaction(instructionData) {
+// Function to send transactions, signed by the payer and any co-signers
+async function sendTransaction(instructionData, coSigners = []) {
     try {
         const transaction = new Transaction();
         const { blockhash, lastValidBlockHeight } = await connection.getLatestBlockhash();
@@ -59,7 +69,7 @@ async function sendTransaction(instructionData) {
         });
         
         // Sign transaction
-        transaction.sign(payer);
+        transaction.sign(payer, ...coSigners);
         console.log('Transaction signatures:', transaction.signatures.map(s => s.publicKey.toBase58()));
 
         // Send and confirm with proper error handling and timeout
@@ -89,8 +99,11 @@ async function sendTransaction(instructionData) {
 // Initialize reward account
 async function initRewardAccount() {
     try {
-        const instructionData = Buffer.alloc(1);
+        const instructionData = Buffer.alloc(4);
         instructionData.writeUInt8(0, 0); // Init variant
+        instructionData.writeUInt8(0, 1); // No idempotency key
+        instructionData.writeUInt8(0, 2); // No referral code
+        instructionData.writeUInt8(0, 3); // Cohort 0
 
         const instruction = new TransactionInstruction({
             programId: PROGRAM_ID,
@@ -98,11 +111,9 @@ async function initRewardAccount() {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true }, // Signer (payer)
                 { pubkey: ACCOUNTS.reward, isSigner: false, isWritable: true }, // Reward account (PDA)
-                { pubkey: ACCOUNTS.userToken, isSigner: false, isWritable: false },
-                { pubkey: ACCOUNTS.vaultToken, isSigner: false, isWritable: false },
                 { pubkey: ACCOUNTS.mint, isSigner: false, isWritable: false },
-                { pubkey: ACCOUNTS.tokenProgram, isSigner: false, isWritable: false },
                 { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // System Program
+                { pubkey: ACCOUNTS.config, isSigner: false, isWritable: true }, // Counts the new user
             ],
         });
 
@@ -113,8 +124,8 @@ async function initRewardAccount() {
     }
 }
 
-// Earn points
-async function earnPoints(points = 100) {
+// Earn points, co-signed by the config's earn authority (the game backend)
+async function earnPoints(points = 100, earnAuthority = payer) {
     try {
         const instructionData = Buffer.alloc(5);
         instructionData.writeUInt8(1, 0); // Earn variant
@@ -126,15 +137,13 @@ async function earnPoints(points = 100) {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: false },
                 { pubkey: ACCOUNTS.reward, isSigner: false, isWritable: true },
-                { pubkey: ACCOUNTS.userToken, isSigner: false, isWritable: true },
-                { pubkey: ACCOUNTS.vaultToken, isSigner: false, isWritable: true },
-                { pubkey: ACCOUNTS.mint, isSigner: false, isWritable: false },
-                { pubkey: ACCOUNTS.tokenProgram, isSigner: false, isWritable: false },
-                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // Only needed to upgrade an older layout
+                { pubkey: ACCOUNTS.config, isSigner: false, isWritable: false },
+                { pubkey: earnAuthority.publicKey, isSigner: true, isWritable: false }, // Earn authority
             ],
         });
 
-        return await sendTransaction(instruction);
+        return await sendTransaction(instruction, [earnAuthority]);
     } catch (error) {
         console.error('Error earning points:', error);
         throw error;
@@ -142,12 +151,12 @@ async function earnPoints(points = 100) {
 }
 
 // Claim reward
-async function claimReward(requiredPoints = 50, amount = 2000n) {
+async function claimReward(requiredPoints = 50) {
     try {
-        const instructionData = Buffer.alloc(13);
+        const instructionData = Buffer.alloc(6);
         instructionData.writeUInt8(2, 0); // Claim variant
         instructionData.writeUInt32LE(requiredPoints, 1);
-        instructionData.writeBigUInt64LE(amount, 5);
+        instructionData.writeUInt8(0, 5); // No expected total_points
 
         const instruction = new TransactionInstruction({
             programId: PROGRAM_ID,
@@ -159,7 +168,8 @@ async function claimReward(requiredPoints = 50, amount = 2000n) {
                 { pubkey: ACCOUNTS.vaultToken, isSigner: false, isWritable: true },
                 { pubkey: ACCOUNTS.mint, isSigner: false, isWritable: false },
                 { pubkey: ACCOUNTS.tokenProgram, isSigner: false, isWritable: false },
-                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: ACCOUNTS.config, isSigner: false, isWritable: true },
+                { pubkey: ACCOUNTS.vaultAuthority, isSigner: false, isWritable: false },
             ],
         });
 
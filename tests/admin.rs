@@ -0,0 +1,463 @@
+mod common;
+
+use common::{default_params, Fixture};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{
+    balance_leaf, hash_pair, ConfigParams, RewardAccount, RewardError, RewardInstruction,
+    BALANCE_TREE_DEPTH, MAX_BURN_BATCH,
+};
+
+fn with_points(total_points: u64) -> RewardAccount {
+    RewardAccount {
+        total_points,
+        ..RewardAccount::default()
+    }
+}
+
+// Admin instruction followed by the given reward accounts
+fn batch_instruction(
+    fixture: &Fixture,
+    data: RewardInstruction,
+    targets: &[Pubkey],
+) -> Instruction {
+    let mut ix = fixture.instruction(data);
+    ix.accounts
+        .extend(targets.iter().map(|key| AccountMeta::new(*key, false)));
+    ix
+}
+
+#[test]
+fn burn_batch_clamps_at_zero_and_tracks_destroyed_points() {
+    let mut fixture = Fixture::new(0);
+    let targets = [50, 10, 0].map(|points| fixture.env.create_reward_account(&with_points(points)));
+
+    let ix = batch_instruction(
+        &fixture,
+        RewardInstruction::BurnBatch { amounts: vec![20] },
+        &targets,
+    );
+    fixture.env.process(ix).unwrap();
+
+    let balances = targets.map(|key| fixture.env.reward_account_at(&key).total_points);
+    assert_eq!(balances, [30, 0, 0]);
+    assert_eq!(fixture.env.config().total_points_destroyed, 30);
+}
+
+#[test]
+fn burn_batch_applies_per_account_amounts() {
+    let mut fixture = Fixture::new(0);
+    let targets = [50, 40].map(|points| fixture.env.create_reward_account(&with_points(points)));
+
+    let ix = batch_instruction(
+        &fixture,
+        RewardInstruction::BurnBatch {
+            amounts: vec![5, 15],
+        },
+        &targets,
+    );
+    fixture.env.process(ix).unwrap();
+
+    let balances = targets.map(|key| fixture.env.reward_account_at(&key).total_points);
+    assert_eq!(balances, [45, 25]);
+    assert_eq!(fixture.env.config().total_points_destroyed, 20);
+}
+
+#[test]
+fn burn_batch_rejects_mismatched_amounts() {
+    let mut fixture = Fixture::new(0);
+    let targets =
+        [50, 40, 30].map(|points| fixture.env.create_reward_account(&with_points(points)));
+
+    let ix = batch_instruction(
+        &fixture,
+        RewardInstruction::BurnBatch {
+            amounts: vec![5, 15],
+        },
+        &targets,
+    );
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn burn_batch_is_bounded() {
+    let mut fixture = Fixture::new(0);
+    let targets: Vec<Pubkey> = (0..=MAX_BURN_BATCH)
+        .map(|_| fixture.env.create_reward_account(&with_points(10)))
+        .collect();
+
+    let ix = batch_instruction(
+        &fixture,
+        RewardInstruction::BurnBatch { amounts: vec![1] },
+        &targets,
+    );
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn burn_batch_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let target = fixture.env.create_reward_account(&with_points(10));
+    let intruder = fixture.env.create_wallet();
+
+    let mut ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::BurnBatch { amounts: vec![10] },
+    );
+    ix.accounts.push(AccountMeta::new(target, false));
+
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.env.reward_account_at(&target).total_points, 10);
+}
+
+// Sibling path for `index` in a BALANCE_TREE_DEPTH tree over `leaves`
+fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let mut level = leaves.to_vec();
+    let mut zero = [0u8; 32];
+    let mut position = index;
+    let mut proof = Vec::new();
+    for _ in 0..BALANCE_TREE_DEPTH {
+        proof.push(level.get(position ^ 1).copied().unwrap_or(zero));
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&zero)))
+            .collect();
+        zero = hash_pair(&zero, &zero);
+        position /= 2;
+    }
+    proof
+}
+
+fn verify_proof(root: [u8; 32], leaf: [u8; 32], index: usize, proof: &[[u8; 32]]) -> bool {
+    let node = proof
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (height, sibling)| {
+            if (index >> height) & 1 == 0 {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            }
+        });
+    node == root
+}
+
+#[test]
+fn balance_root_commits_to_every_page() {
+    let mut fixture = Fixture::new(0);
+    let points = [70, 5, 1_234];
+    let owners = points.map(|_| Pubkey::new_unique());
+    let targets: Vec<Pubkey> = owners
+        .iter()
+        .zip(points)
+        .map(|(owner, p)| {
+            fixture.env.create_reward_account(&RewardAccount {
+                owner: *owner,
+                ..with_points(p)
+            })
+        })
+        .collect();
+
+    let first = batch_instruction(
+        &fixture,
+        RewardInstruction::ComputeBalanceRoot {
+            first_page: true,
+            last_page: false,
+        },
+        &targets[..2],
+    );
+    fixture.env.process(first).unwrap();
+    assert!(!fixture.env.config().balance_root.finalized);
+
+    let last = batch_instruction(
+        &fixture,
+        RewardInstruction::ComputeBalanceRoot {
+            first_page: false,
+            last_page: true,
+        },
+        &targets[2..],
+    );
+    fixture.env.process(last).unwrap();
+
+    let balance_root = fixture.env.config().balance_root;
+    assert!(balance_root.finalized);
+    assert_eq!(balance_root.leaf_count, 3);
+
+    // Leaves are keyed by the owning wallet, not the reward PDA
+    let leaves: Vec<[u8; 32]> = owners
+        .iter()
+        .zip(points)
+        .map(|(owner, p)| balance_leaf(owner, p))
+        .collect();
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = merkle_proof(&leaves, index);
+        assert!(verify_proof(balance_root.root, *leaf, index, &proof));
+    }
+
+    let by_address = balance_leaf(&targets[1], points[1]);
+    assert!(!verify_proof(
+        balance_root.root,
+        by_address,
+        1,
+        &merkle_proof(&leaves, 1)
+    ));
+
+    // A forged balance does not verify against the root
+    let forged = balance_leaf(&owners[1], 5_000);
+    assert!(!verify_proof(
+        balance_root.root,
+        forged,
+        1,
+        &merkle_proof(&leaves, 1)
+    ));
+}
+
+#[test]
+fn balance_root_requires_a_new_snapshot_after_finalizing() {
+    let mut fixture = Fixture::new(0);
+    let target = fixture.env.create_reward_account(&with_points(10));
+
+    let single_page = RewardInstruction::ComputeBalanceRoot {
+        first_page: true,
+        last_page: true,
+    };
+    let ix = batch_instruction(&fixture, single_page, &[target]);
+    fixture.env.process(ix).unwrap();
+
+    let ix = batch_instruction(
+        &fixture,
+        RewardInstruction::ComputeBalanceRoot {
+            first_page: false,
+            last_page: true,
+        },
+        &[target],
+    );
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.env.config().balance_root.leaf_count, 1);
+}
+
+fn adjust_lifetime(fixture: &mut Fixture, delta: i64) -> Result<(), ProgramError> {
+    let user = fixture.signer;
+    fixture.process(RewardInstruction::AdjustLifetime { user, delta })
+}
+
+#[test]
+fn earn_accrues_lifetime_points() {
+    let mut fixture = Fixture::new(0);
+
+    fixture.earn(30).unwrap();
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 10,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 20);
+    assert_eq!(reward_account.lifetime_points, 30);
+}
+
+#[test]
+fn adjust_lifetime_applies_positive_and_negative_deltas() {
+    let mut fixture = Fixture::new(0);
+    fixture.earn(100).unwrap();
+
+    adjust_lifetime(&mut fixture, 50).unwrap();
+    assert_eq!(fixture.reward_account().lifetime_points, 150);
+
+    adjust_lifetime(&mut fixture, -120).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.lifetime_points, 30);
+    // Only the lifetime counter is corrected
+    assert_eq!(reward_account.total_points, 100);
+}
+
+#[test]
+fn adjust_lifetime_saturates_at_the_bounds() {
+    let mut fixture = Fixture::new(0);
+
+    adjust_lifetime(&mut fixture, -5).unwrap();
+    assert_eq!(fixture.reward_account().lifetime_points, 0);
+
+    // The counter is wider than a single delta
+    adjust_lifetime(&mut fixture, i64::MAX).unwrap();
+    assert_eq!(fixture.reward_account().lifetime_points, i64::MAX as u64);
+    for _ in 0..2 {
+        adjust_lifetime(&mut fixture, i64::MAX).unwrap();
+    }
+    assert_eq!(fixture.reward_account().lifetime_points, u64::MAX);
+}
+
+#[test]
+fn adjust_lifetime_targets_the_given_user() {
+    let mut fixture = Fixture::new(0);
+    let user = fixture.env.create_wallet();
+    fixture.env.set_reward_account(&user, &with_points(10));
+
+    let mut ix = fixture.instruction(RewardInstruction::AdjustLifetime { user, delta: 25 });
+    ix.accounts[1].pubkey = fixture.env.reward_address(&user);
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.env.reward_account(&user).lifetime_points, 25);
+    assert_eq!(fixture.reward_account().lifetime_points, 0);
+}
+
+#[test]
+fn adjust_lifetime_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::AdjustLifetime {
+            user: intruder,
+            delta: 1_000,
+        },
+    );
+
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.reward_account().lifetime_points, 0);
+}
+
+const PROGRAM_END_TS: i64 = 1_700_000_000;
+
+fn ending_fixture() -> Fixture {
+    Fixture::with_params(
+        0,
+        ConfigParams {
+            program_end_ts: PROGRAM_END_TS,
+            ..default_params()
+        },
+    )
+}
+
+#[test]
+fn close_config_after_the_program_ends() {
+    let mut fixture = ending_fixture();
+    let config_address = fixture.env.config_address();
+    let rent = fixture.env.lamports(&config_address);
+    let admin_lamports = fixture.env.lamports(&fixture.signer);
+    fixture.env.set_clock(PROGRAM_END_TS);
+
+    fixture
+        .process(RewardInstruction::CloseConfig { force: false })
+        .unwrap();
+
+    assert!(fixture.env.account(&config_address).is_none());
+    assert_eq!(fixture.env.lamports(&fixture.signer), admin_lamports + rent);
+}
+
+#[test]
+fn close_config_is_rejected_while_live() {
+    let mut fixture = ending_fixture();
+    fixture.env.set_clock(PROGRAM_END_TS - 1);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::CloseConfig { force: false }),
+        Err(RewardError::ProgramStillLive.into())
+    );
+    assert!(fixture.env.account(&fixture.env.config_address()).is_some());
+
+    // The admin may still force it
+    fixture
+        .process(RewardInstruction::CloseConfig { force: true })
+        .unwrap();
+    assert!(fixture.env.account(&fixture.env.config_address()).is_none());
+}
+
+#[test]
+fn close_config_requires_admin() {
+    let mut fixture = ending_fixture();
+    let intruder = fixture.env.create_wallet();
+    fixture.env.set_clock(PROGRAM_END_TS);
+
+    let ix =
+        fixture.instruction_signed_by(&intruder, RewardInstruction::CloseConfig { force: true });
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+}
+
+#[test]
+fn init_config_requires_the_upgrade_authority() {
+    let mut fixture = ending_fixture();
+    let intruder = fixture.env.create_wallet();
+    fixture
+        .process(RewardInstruction::CloseConfig { force: true })
+        .unwrap();
+
+    // Closing the config does not let anyone else take the program over
+    let init_config = RewardInstruction::InitConfig {
+        params: default_params(),
+    };
+    let ix = fixture.instruction_signed_by(&intruder, init_config);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(fixture.env.account(&fixture.env.config_address()).is_none());
+
+    fixture
+        .process(RewardInstruction::InitConfig {
+            params: default_params(),
+        })
+        .unwrap();
+    assert_eq!(fixture.env.config().admin, fixture.signer);
+    assert_eq!(fixture.env.config().mint, fixture.mint);
+}
+
+#[test]
+fn update_config_retunes_claims() {
+    let mut fixture = Fixture::new(100);
+    let params = ConfigParams {
+        claim_point_fee: 5,
+        ..default_params()
+    };
+
+    fixture
+        .process(RewardInstruction::UpdateConfig {
+            params: params.clone(),
+        })
+        .unwrap();
+    assert_eq!(fixture.env.config().params, params);
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        })
+        .unwrap();
+    assert_eq!(fixture.reward_account().total_points, 55);
+}
+
+#[test]
+fn update_config_leaves_the_exchange_rate_to_the_timelock() {
+    let mut fixture = Fixture::new(100);
+    let params = default_params();
+
+    let result = fixture.process(RewardInstruction::UpdateConfig {
+        params: ConfigParams {
+            exchange_rate: 2 * params.exchange_rate,
+            ..params.clone()
+        },
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+
+    let intruder = fixture.env.create_wallet();
+    let ix = fixture.instruction_signed_by(&intruder, RewardInstruction::UpdateConfig { params });
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+}
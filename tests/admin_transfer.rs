@@ -0,0 +1,97 @@
+mod common;
+
+use common::Fixture;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use wagus_reward_system::RewardInstruction;
+
+fn propose(fixture: &mut Fixture, new_admin: Pubkey) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::ProposeAdmin { new_admin })
+}
+
+fn accept(fixture: &mut Fixture, signer: &Pubkey) -> Result<(), ProgramError> {
+    let ix = fixture.instruction_signed_by(signer, RewardInstruction::AcceptAdmin);
+    fixture.env.process(ix)
+}
+
+#[test]
+fn proposed_admin_takes_over_once_it_accepts() {
+    let mut fixture = Fixture::new(0);
+    let successor = fixture.env.create_wallet();
+
+    propose(&mut fixture, successor).unwrap();
+    // Nothing changes until the successor signs
+    let config = fixture.env.config();
+    assert_eq!(config.admin, fixture.signer);
+    assert_eq!(config.pending_admin, successor);
+
+    accept(&mut fixture, &successor).unwrap();
+    let config = fixture.env.config();
+    assert_eq!(config.admin, successor);
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    // The previous admin is locked out
+    let previous = fixture.signer;
+    assert_eq!(
+        propose(&mut fixture, previous),
+        Err(ProgramError::IncorrectAuthority)
+    );
+}
+
+#[test]
+fn only_the_pending_admin_can_accept() {
+    let mut fixture = Fixture::new(0);
+    let successor = fixture.env.create_wallet();
+    let intruder = fixture.env.create_wallet();
+
+    // Nobody is pending yet
+    assert_eq!(
+        accept(&mut fixture, &successor),
+        Err(ProgramError::IncorrectAuthority)
+    );
+
+    propose(&mut fixture, successor).unwrap();
+    assert_eq!(
+        accept(&mut fixture, &intruder),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.env.config().admin, fixture.signer);
+}
+
+#[test]
+fn admin_can_replace_or_withdraw_a_proposal() {
+    let mut fixture = Fixture::new(0);
+    let mistyped = fixture.env.create_wallet();
+    let successor = fixture.env.create_wallet();
+
+    propose(&mut fixture, mistyped).unwrap();
+    propose(&mut fixture, successor).unwrap();
+    assert_eq!(
+        accept(&mut fixture, &mistyped),
+        Err(ProgramError::IncorrectAuthority)
+    );
+
+    propose(&mut fixture, Pubkey::default()).unwrap();
+    assert_eq!(
+        accept(&mut fixture, &successor),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.env.config().admin, fixture.signer);
+}
+
+#[test]
+fn propose_admin_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::ProposeAdmin {
+            new_admin: intruder,
+        },
+    );
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.env.config().pending_admin, Pubkey::default());
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{Fixture, EXCHANGE_RATE};
+use solana_program::program_error::ProgramError;
+use wagus_reward_system::{
+    BlockReason, Eligibility, RewardError, RewardInstruction, UserStatus, ACTION_CLAIM,
+};
+
+fn archived_fixture() -> Fixture {
+    let mut fixture = Fixture::new(100);
+    fixture.process(RewardInstruction::Archive).unwrap();
+    fixture
+}
+
+#[test]
+fn archive_rejects_mutations() {
+    let mut fixture = archived_fixture();
+    assert!(fixture.env.config().archived);
+
+    let mutations = [
+        || RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        },
+        || RewardInstruction::ClaimAndStake {
+            required_points: 40,
+            amount: 40 * EXCHANGE_RATE,
+        },
+        || RewardInstruction::BurnBatch { amounts: vec![10] },
+        || RewardInstruction::MigrateLegacyLayout,
+        || RewardInstruction::ComputeBalanceRoot {
+            first_page: true,
+            last_page: true,
+        },
+    ];
+    for mutation in mutations {
+        assert_eq!(
+            fixture.process(mutation()),
+            Err(RewardError::ProgramArchived.into())
+        );
+    }
+    assert_eq!(fixture.earn(10), Err(RewardError::ProgramArchived.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn archive_keeps_balances_queryable() {
+    let mut fixture = archived_fixture();
+
+    fixture.process(RewardInstruction::GetFullStatus).unwrap();
+    let status = UserStatus::try_from_slice(&fixture.env.return_data().unwrap()).unwrap();
+    assert_eq!(status.total_points, 100);
+
+    fixture
+        .process(RewardInstruction::CanPerform {
+            action: ACTION_CLAIM,
+        })
+        .unwrap();
+    let eligibility = Eligibility::try_from_slice(&fixture.env.return_data().unwrap()).unwrap();
+    assert_eq!(eligibility.reason, BlockReason::ProgramArchived);
+}
+
+#[test]
+fn archive_cannot_be_undone() {
+    let mut fixture = archived_fixture();
+
+    assert_eq!(
+        fixture.process(RewardInstruction::Archive),
+        Err(RewardError::ProgramArchived.into())
+    );
+    // Closing the config and starting over would lift the archive
+    assert_eq!(
+        fixture.process(RewardInstruction::CloseConfig { force: true }),
+        Err(RewardError::ProgramArchived.into())
+    );
+    assert!(fixture.env.config().archived);
+}
+
+#[test]
+fn archive_requires_admin() {
+    let mut fixture = Fixture::new(100);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = fixture.instruction_signed_by(&intruder, RewardInstruction::Archive);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(!fixture.env.config().archived);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{Fixture, VAULT_BALANCE};
+use solana_program::{instruction::AccountMeta, program_error::ProgramError};
+use wagus_reward_system::{RewardAccount, RewardError, RewardInstruction};
+
+const BURN_AMOUNT: u64 = 250_000;
+
+// A token account held by the signer, put in the vault slot to burn from
+fn owned_token_fixture() -> Fixture {
+    let mut fixture = Fixture::new(0);
+    fixture.vault_token =
+        fixture
+            .env
+            .create_token_account(&fixture.mint, &fixture.signer, VAULT_BALANCE);
+    fixture
+}
+
+fn supply(fixture: &Fixture) -> u64 {
+    fixture.env.mint(&fixture.mint).unwrap().supply
+}
+
+#[test]
+fn admin_burns_from_the_program_vault() {
+    let mut fixture = Fixture::new(0);
+    let supply_before = supply(&fixture);
+
+    let mut ix = fixture.instruction(RewardInstruction::Burn {
+        amount: BURN_AMOUNT,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        fixture.env.vault_authority_address(),
+        false,
+    ));
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(supply(&fixture), supply_before - BURN_AMOUNT);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - BURN_AMOUNT
+    );
+}
+
+#[test]
+fn owner_burns_from_their_own_token_account() {
+    let mut fixture = owned_token_fixture();
+    let supply_before = supply(&fixture);
+
+    fixture
+        .process(RewardInstruction::Burn {
+            amount: BURN_AMOUNT,
+        })
+        .unwrap();
+
+    assert_eq!(supply(&fixture), supply_before - BURN_AMOUNT);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - BURN_AMOUNT
+    );
+}
+
+#[test]
+fn burning_the_program_vault_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+    let mint = fixture.mint;
+    fixture.env.set_reward_account(
+        &intruder,
+        &RewardAccount {
+            mint,
+            ..RewardAccount::default()
+        },
+    );
+
+    let mut ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::Burn {
+            amount: BURN_AMOUNT,
+        },
+    );
+    ix.accounts.push(AccountMeta::new_readonly(
+        fixture.env.vault_authority_address(),
+        false,
+    ));
+
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn burn_rejects_a_foreign_mint() {
+    let mut fixture = owned_token_fixture();
+    let foreign_mint = fixture.env.create_mint(&fixture.signer, 6);
+
+    let mut ix = fixture.instruction(RewardInstruction::Burn {
+        amount: BURN_AMOUNT,
+    });
+    ix.accounts[2].pubkey = foreign_mint;
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+}
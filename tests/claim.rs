@@ -0,0 +1,645 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE, VAULT_BALANCE};
+use solana_program::{instruction::AccountMeta, program_error::ProgramError};
+use wagus_reward_system::{
+    ConfigParams, RewardAccount, RewardError, RewardEvent, RewardInstruction,
+};
+
+#[test]
+fn claim_and_stake_restakes_payout_as_points() {
+    let mut fixture = Fixture::new(100);
+
+    fixture
+        .process(RewardInstruction::ClaimAndStake {
+            required_points: 40,
+            amount: 40 * EXCHANGE_RATE,
+        })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 60);
+    assert_eq!(reward_account.staked_points, 40);
+    assert_eq!(reward_account.rewards_claimed, 1);
+
+    // The payout stays on-chain as points, no tokens move
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn claim_and_stake_rounds_partial_points_down() {
+    let mut fixture = Fixture::new(100);
+
+    fixture
+        .process(RewardInstruction::ClaimAndStake {
+            required_points: 10,
+            amount: 5 * EXCHANGE_RATE + EXCHANGE_RATE / 2,
+        })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 90);
+    assert_eq!(reward_account.staked_points, 5);
+}
+
+#[test]
+fn claim_and_stake_rejects_amount_worth_more_than_points() {
+    let mut fixture = Fixture::new(100);
+
+    let result = fixture.process(RewardInstruction::ClaimAndStake {
+        required_points: 10,
+        amount: 10 * EXCHANGE_RATE + 1,
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn claim_and_stake_requires_enough_points() {
+    let mut fixture = Fixture::new(5);
+
+    let result = fixture.process(RewardInstruction::ClaimAndStake {
+        required_points: 10,
+        amount: 10 * EXCHANGE_RATE,
+    });
+    assert_eq!(result, Err(RewardError::NotEnoughPoints.into()));
+    assert_eq!(fixture.reward_account().staked_points, 0);
+}
+
+fn point_fee_fixture(total_points: u64, claim_point_fee: u32) -> Fixture {
+    Fixture::with_params(
+        total_points,
+        ConfigParams {
+            claim_point_fee,
+            ..default_params()
+        },
+    )
+}
+
+#[test]
+fn claim_burns_the_point_fee() {
+    let mut fixture = point_fee_fixture(100, 5);
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 55);
+    assert_eq!(fixture.env.config().total_points_destroyed, 5);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_rejects_balance_that_cannot_cover_the_point_fee() {
+    let mut fixture = point_fee_fixture(42, 5);
+
+    let result = fixture.process(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+
+    assert_eq!(result, Err(RewardError::NotEnoughPoints.into()));
+    assert_eq!(fixture.reward_account().total_points, 42);
+    assert_eq!(fixture.env.config().total_points_destroyed, 0);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+const REPLENISH_AMOUNT: u64 = 500_000;
+
+// A vault that counts as low, replenished by the mint authority PDA
+fn replenishing_fixture(max_supply: u64) -> Fixture {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            auto_replenish: true,
+            replenish_threshold: VAULT_BALANCE + 1,
+            replenish_amount: REPLENISH_AMOUNT,
+            max_supply,
+            ..default_params()
+        },
+    );
+    let mint_authority = fixture.env.mint_authority_address();
+    fixture
+        .env
+        .set_mint_authority(&fixture.mint, &mint_authority);
+    fixture
+}
+
+fn claim_with_replenish(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    let mut ix = fixture.instruction(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        fixture.env.mint_authority_address(),
+        false,
+    ));
+    fixture.env.process(ix)
+}
+
+#[test]
+fn claim_replenishes_a_low_vault() {
+    let mut fixture = replenishing_fixture(10 * VAULT_BALANCE);
+
+    claim_with_replenish(&mut fixture).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE + REPLENISH_AMOUNT - 40 * EXCHANGE_RATE
+    );
+    assert_eq!(
+        fixture.env.mint(&fixture.mint).unwrap().supply,
+        VAULT_BALANCE + REPLENISH_AMOUNT
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+    assert_eq!(fixture.env.config().total_minted, REPLENISH_AMOUNT);
+    let minted = borsh::to_vec(&RewardEvent::Minted {
+        amount: REPLENISH_AMOUNT,
+    })
+    .unwrap();
+    assert_eq!(fixture.env.program_data()[0], minted);
+}
+
+#[test]
+fn claim_skips_replenishment_past_the_supply_cap() {
+    let mut fixture = replenishing_fixture(REPLENISH_AMOUNT - 1);
+
+    claim_with_replenish(&mut fixture).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - 40 * EXCHANGE_RATE
+    );
+    assert_eq!(
+        fixture.env.mint(&fixture.mint).unwrap().supply,
+        VAULT_BALANCE
+    );
+}
+
+// Claims capped at 5% of the vault, i.e. 50 points worth
+fn vault_fraction_fixture() -> Fixture {
+    Fixture::with_params(
+        100,
+        ConfigParams {
+            max_vault_fraction_bps: 500,
+            ..default_params()
+        },
+    )
+}
+
+#[test]
+fn claim_within_the_vault_fraction_cap() {
+    let mut fixture = vault_fraction_fixture();
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 50,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        VAULT_BALANCE / 20
+    );
+}
+
+#[test]
+fn claim_above_the_vault_fraction_cap_is_rejected() {
+    let mut fixture = vault_fraction_fixture();
+
+    let result = fixture.process(RewardInstruction::Claim {
+        required_points: 60,
+        expected_total_points: None,
+    });
+
+    assert_eq!(result, Err(RewardError::ExceedsVaultFractionCap.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn claim_of_zero_points_is_rejected() {
+    let mut fixture = Fixture::new(100);
+
+    let result = fixture.process(RewardInstruction::Claim {
+        required_points: 0,
+        expected_total_points: None,
+    });
+
+    assert_eq!(result, Err(RewardError::PayoutRoundsToZero.into()));
+    assert_eq!(fixture.reward_account().rewards_claimed, 0);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn claim_and_stake_rejects_a_payout_rounding_to_zero() {
+    let mut fixture = Fixture::new(100);
+
+    let result = fixture.process(RewardInstruction::ClaimAndStake {
+        required_points: 1,
+        amount: EXCHANGE_RATE - 1,
+    });
+
+    assert_eq!(result, Err(RewardError::PayoutRoundsToZero.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn claim_and_stake_accumulates_dust_into_whole_points() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            accumulate_dust: true,
+            ..default_params()
+        },
+    );
+    let restake_dust = || RewardInstruction::ClaimAndStake {
+        required_points: 1,
+        amount: EXCHANGE_RATE * 3 / 4,
+    };
+
+    fixture.process(restake_dust()).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.staked_points, 0);
+    assert_eq!(reward_account.dust_credit, EXCHANGE_RATE * 3 / 4);
+
+    // The second remainder tips the credit over a whole point
+    fixture.process(restake_dust()).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.staked_points, 1);
+    assert_eq!(reward_account.dust_credit, EXCHANGE_RATE / 2);
+    assert_eq!(reward_account.total_points, 98);
+}
+
+fn claim_forty(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    })
+}
+
+#[test]
+fn claim_rejects_a_vault_of_another_mint() {
+    let mut fixture = Fixture::new(100);
+    let other_mint = fixture.env.create_mint(&fixture.signer, 6);
+    fixture.vault_token =
+        fixture
+            .env
+            .create_token_account(&other_mint, &fixture.signer, VAULT_BALANCE);
+
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(RewardError::WrongMint.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn claim_rejects_a_user_token_account_owned_by_someone_else() {
+    let mut fixture = Fixture::new(100);
+    let stranger = fixture.env.create_wallet();
+    fixture.user_token = fixture
+        .env
+        .create_token_account(&fixture.mint, &stranger, 0);
+
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(RewardError::TokenOwnerMismatch.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn claim_rejects_a_rewards_claimed_overflow() {
+    let mut fixture = Fixture::new(100);
+    let mut reward_account = fixture.reward_account();
+    reward_account.rewards_claimed = u32::MAX;
+    fixture.set_reward_account(&reward_account);
+
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(ProgramError::ArithmeticOverflow)
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+
+    // One below the limit still goes through
+    reward_account.rewards_claimed = u32::MAX - 1;
+    fixture.set_reward_account(&reward_account);
+    claim_forty(&mut fixture).unwrap();
+    assert_eq!(fixture.reward_account().rewards_claimed, u32::MAX);
+}
+
+fn claim_expecting(fixture: &mut Fixture, expected_total_points: u64) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: Some(expected_total_points),
+    })
+}
+
+#[test]
+fn claim_with_the_expected_balance_pays_out() {
+    let mut fixture = Fixture::new(100);
+
+    claim_expecting(&mut fixture, 100).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_against_a_stale_balance_is_rejected() {
+    let mut fixture = Fixture::new(100);
+
+    assert_eq!(
+        claim_expecting(&mut fixture, 120),
+        Err(RewardError::BalanceChanged.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+// A vault holding 50 points worth of tokens above its reserve
+fn reserve_fixture() -> Fixture {
+    Fixture::with_params(
+        100,
+        ConfigParams {
+            vault_reserve: VAULT_BALANCE - 50 * EXCHANGE_RATE,
+            ..default_params()
+        },
+    )
+}
+
+#[test]
+fn claim_above_the_vault_reserve_pays_out() {
+    let mut fixture = reserve_fixture();
+
+    claim_forty(&mut fixture).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - 40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_into_the_vault_reserve_is_rejected() {
+    let mut fixture = reserve_fixture();
+
+    let result = fixture.process(RewardInstruction::Claim {
+        required_points: 60,
+        expected_total_points: None,
+    });
+
+    assert_eq!(result, Err(RewardError::VaultReserveProtected.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn claim_pays_out_at_the_exchange_rate() {
+    let mut fixture = Fixture::new(100);
+
+    claim_forty(&mut fixture).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_amount_overflow_is_rejected() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            exchange_rate: u64::MAX,
+            ..default_params()
+        },
+    );
+
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(ProgramError::ArithmeticOverflow)
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn claim_beyond_the_vault_balance_fails() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            exchange_rate: VAULT_BALANCE / 20,
+            ..default_params()
+        },
+    );
+
+    assert!(claim_forty(&mut fixture).is_err());
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+// 30 liquid and 50 staked points, with a 10% penalty on drawn stake
+fn stake_draw_fixture() -> Fixture {
+    let mut fixture = Fixture::with_params(
+        30,
+        ConfigParams {
+            allow_stake_draw: true,
+            stake_draw_penalty_bps: 1_000,
+            ..default_params()
+        },
+    );
+    let reward_account = RewardAccount {
+        staked_points: 50,
+        ..fixture.reward_account()
+    };
+    fixture.set_reward_account(&reward_account);
+    fixture
+}
+
+#[test]
+fn claim_covered_by_liquid_points_leaves_the_stake_alone() {
+    let mut fixture = stake_draw_fixture();
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 25,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 5);
+    assert_eq!(reward_account.staked_points, 50);
+    assert_eq!(fixture.env.config().total_points_destroyed, 0);
+}
+
+#[test]
+fn claim_draws_the_shortfall_from_the_stake_with_a_penalty() {
+    let mut fixture = stake_draw_fixture();
+
+    // 10 points short, drawn from the stake along with a 1 point penalty
+    claim_forty(&mut fixture).unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 0);
+    assert_eq!(reward_account.staked_points, 39);
+    assert_eq!(reward_account.rewards_claimed, 1);
+    assert_eq!(fixture.env.config().total_points_destroyed, 1);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_beyond_liquid_and_staked_points_is_rejected() {
+    let mut fixture = stake_draw_fixture();
+
+    // 50 short needs 55 staked points once the penalty is added
+    let result = fixture.process(RewardInstruction::Claim {
+        required_points: 80,
+        expected_total_points: None,
+    });
+
+    assert_eq!(result, Err(RewardError::NotEnoughPoints.into()));
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 30);
+    assert_eq!(reward_account.staked_points, 50);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+fn stake(fixture: &mut Fixture, points: u32) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::ClaimAndStake {
+        required_points: points,
+        amount: points as u64 * EXCHANGE_RATE,
+    })
+}
+
+#[test]
+fn claim_and_stake_up_to_the_per_user_cap() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            max_staked_per_user: 50,
+            ..default_params()
+        },
+    );
+
+    stake(&mut fixture, 30).unwrap();
+    stake(&mut fixture, 20).unwrap();
+    assert_eq!(
+        stake(&mut fixture, 1),
+        Err(RewardError::MaxStakeExceeded.into())
+    );
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.staked_points, 50);
+    assert_eq!(reward_account.total_points, 50);
+}
+
+#[test]
+fn one_claim_per_epoch() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            one_claim_per_epoch: true,
+            ..default_params()
+        },
+    );
+    fixture.env.set_epoch(400);
+
+    claim_forty(&mut fixture).unwrap();
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(RewardError::AlreadyClaimedThisEpoch.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 60);
+
+    fixture.env.set_epoch(401);
+    claim_forty(&mut fixture).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 20);
+    assert_eq!(reward_account.next_claim_epoch, 402);
+}
+
+#[test]
+fn one_claim_per_epoch_holds_in_epoch_zero() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            one_claim_per_epoch: true,
+            ..default_params()
+        },
+    );
+    fixture.env.set_epoch(0);
+
+    claim_forty(&mut fixture).unwrap();
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(RewardError::AlreadyClaimedThisEpoch.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 60);
+}
+
+const CLAIM_COOLDOWN_SECS: u32 = 86_400;
+
+#[test]
+fn claim_within_the_cooldown_is_rejected() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            claim_cooldown_secs: CLAIM_COOLDOWN_SECS,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    claim_forty(&mut fixture).unwrap();
+
+    fixture
+        .env
+        .set_clock(1_000 + CLAIM_COOLDOWN_SECS as i64 - 1);
+    assert_eq!(
+        claim_forty(&mut fixture),
+        Err(RewardError::ClaimCooldown.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 60);
+
+    fixture.env.set_clock(1_000 + CLAIM_COOLDOWN_SECS as i64);
+    claim_forty(&mut fixture).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 20);
+    assert_eq!(
+        reward_account.last_claim_ts,
+        1_000 + CLAIM_COOLDOWN_SECS as i64
+    );
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::Fixture;
+use solana_program::instruction::AccountMeta;
+use wagus_reward_system::{RewardError, RewardInstruction};
+
+#[test]
+fn close_reward_returns_the_rent_to_the_signer() {
+    let mut fixture = Fixture::new(0);
+    let reward_address = fixture.reward_address();
+    let rent = fixture.env.lamports(&reward_address);
+    let signer_lamports = fixture.env.lamports(&fixture.signer);
+
+    fixture
+        .process(RewardInstruction::CloseReward { force: false })
+        .unwrap();
+
+    assert!(fixture.env.account(&reward_address).is_none());
+    assert_eq!(
+        fixture.env.lamports(&fixture.signer),
+        signer_lamports + rent
+    );
+    // Nothing is left to earn into
+    assert!(fixture.earn(10).is_err());
+}
+
+#[test]
+fn close_reward_pays_a_given_destination() {
+    let mut fixture = Fixture::new(0);
+    let destination = fixture.env.create_wallet();
+    let destination_lamports = fixture.env.lamports(&destination);
+    let rent = fixture.env.lamports(&fixture.reward_address());
+
+    let mut ix = fixture.instruction(RewardInstruction::CloseReward { force: false });
+    ix.accounts.push(AccountMeta::new(destination, false));
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(
+        fixture.env.lamports(&destination),
+        destination_lamports + rent
+    );
+}
+
+#[test]
+fn close_reward_with_points_needs_force() {
+    let mut fixture = Fixture::new(25);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::CloseReward { force: false }),
+        Err(RewardError::RewardAccountNotEmpty.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 25);
+
+    fixture
+        .process(RewardInstruction::CloseReward { force: true })
+        .unwrap();
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+#[test]
+fn close_reward_with_staked_or_pending_points_needs_force() {
+    for (staked_points, pending_points) in [(10, 0), (0, 3)] {
+        let mut fixture = Fixture::new(0);
+        let mut reward_account = fixture.reward_account();
+        reward_account.staked_points = staked_points;
+        reward_account.pending_points = pending_points;
+        fixture.set_reward_account(&reward_account);
+
+        assert_eq!(
+            fixture.process(RewardInstruction::CloseReward { force: false }),
+            Err(RewardError::RewardAccountNotEmpty.into())
+        );
+        fixture
+            .process(RewardInstruction::CloseReward { force: true })
+            .unwrap();
+        assert!(fixture.env.account(&fixture.reward_address()).is_none());
+    }
+}
+
+#[test]
+fn close_reward_belongs_to_the_owner_not_the_authority() {
+    let mut fixture = Fixture::new(0);
+    let authority = fixture.env.create_wallet();
+    let mut reward_account = fixture.reward_account();
+    reward_account.authority = authority;
+    fixture.set_reward_account(&reward_account);
+
+    // The Earn authority cannot close the owner's account
+    let mut ix =
+        fixture.instruction_signed_by(&authority, RewardInstruction::CloseReward { force: true });
+    ix.accounts[1].pubkey = fixture.reward_address();
+    assert_eq!(fixture.env.process(ix), Err(RewardError::InvalidPda.into()));
+    assert!(fixture.env.account(&fixture.reward_address()).is_some());
+
+    fixture
+        .process(RewardInstruction::CloseReward { force: true })
+        .unwrap();
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{default_params, Fixture, EARN_AUTHORITY};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction, BPS_DENOMINATOR};
+
+// Cohort 0 earns at face value, cohort 1 gets a 50% boost
+fn cohort_fixture() -> Fixture {
+    Fixture::configured(ConfigParams {
+        cohort_count: 2,
+        cohort_earn_bps: [BPS_DENOMINATOR, 15_000, 0, 0],
+        ..default_params()
+    })
+}
+
+fn init_in_cohort(fixture: &mut Fixture, wallet: &Pubkey, cohort: u8) -> Result<(), ProgramError> {
+    let ix = fixture.instruction_signed_by(
+        wallet,
+        RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort,
+        },
+    );
+    fixture.env.process(ix)
+}
+
+#[test]
+fn cohorts_are_credited_their_own_share_of_each_earn() {
+    let mut fixture = cohort_fixture();
+    let control = fixture.signer;
+    let boosted = fixture.env.create_wallet();
+    init_in_cohort(&mut fixture, &control, 0).unwrap();
+    init_in_cohort(&mut fixture, &boosted, 1).unwrap();
+    assert_eq!(fixture.env.reward_account(&boosted).cohort, 1);
+
+    for wallet in [control, boosted] {
+        let ix = fixture.earn_instruction(&wallet, &EARN_AUTHORITY, 100);
+        fixture.env.process(ix).unwrap();
+    }
+
+    assert_eq!(fixture.env.reward_account(&control).total_points, 100);
+    assert_eq!(fixture.env.reward_account(&boosted).total_points, 150);
+}
+
+#[test]
+fn init_into_an_unconfigured_cohort_is_rejected() {
+    let mut fixture = cohort_fixture();
+    let signer = fixture.signer;
+
+    assert_eq!(
+        init_in_cohort(&mut fixture, &signer, 2),
+        Err(RewardError::UnknownCohort.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+
+    // Without cohorts only cohort 0 exists
+    let mut fixture = Fixture::configured(default_params());
+    let signer = fixture.signer;
+    assert_eq!(
+        init_in_cohort(&mut fixture, &signer, 1),
+        Err(RewardError::UnknownCohort.into())
+    );
+}
+
+#[test]
+fn configured_cohorts_must_earn_a_non_zero_share() {
+    let mut fixture = cohort_fixture();
+
+    let result = fixture.process(RewardInstruction::UpdateConfig {
+        params: ConfigParams {
+            cohort_count: 2,
+            cohort_earn_bps: [BPS_DENOMINATOR, 0, 0, 0],
+            ..default_params()
+        },
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.env.config().params.cohort_earn_bps[1], 15_000);
+
+    // Shares beyond cohort_count are unused and may stay zero
+    fixture
+        .process(RewardInstruction::UpdateConfig {
+            params: ConfigParams {
+                cohort_count: 1,
+                cohort_earn_bps: [BPS_DENOMINATOR, 0, 0, 0],
+                ..default_params()
+            },
+        })
+        .unwrap();
+    assert_eq!(fixture.env.config().params.cohort_count, 1);
+}
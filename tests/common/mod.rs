@@ -0,0 +1,850 @@
+// Shared harness for the program tests.
+//
+// Instructions are run by calling `process_instruction` directly against an
+// in-memory ledger. Sysvars, return data and CPIs into the system and
+// SPL Token programs are served by custom syscall stubs, so the tests exercise
+// the real processor without needing a validator.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Once;
+
+use borsh::BorshDeserialize;
+use solana_instruction::{BorrowedAccountMeta, BorrowedInstruction};
+use solana_instructions_sysvar::construct_instructions_data;
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    clock::Clock,
+    ed25519_program,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program, sysvar,
+};
+use wagus_reward_system::{
+    pda, process_instruction, AccountState, Config, ConfigParams, RewardAccount, RewardInstruction,
+    BPS_DENOMINATOR, DISCRIMINATOR_LEN,
+};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+    static PROGRAM_ID: RefCell<Pubkey> = RefCell::new(Pubkey::default());
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+    static PROGRAM_DATA: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|data| data.borrow().clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let program_id = PROGRAM_ID.with(|id| *id.borrow());
+        RETURN_DATA.with(|slot| *slot.borrow_mut() = Some((program_id, data.to_vec())));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        PROGRAM_DATA.with(|data| {
+            data.borrow_mut()
+                .extend(fields.iter().map(|field| field.to_vec()))
+        });
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = PROGRAM_ID.with(|id| *id.borrow());
+        let mut infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let mut info = account_infos
+                .iter()
+                .find(|info| info.key == &meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?
+                .clone();
+            if meta.is_signer && !info.is_signer {
+                let signed = signers_seeds.iter().any(|seeds| {
+                    Pubkey::create_program_address(seeds, &caller)
+                        .map(|key| key == meta.pubkey)
+                        .unwrap_or(false)
+                });
+                if !signed {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                info.is_signer = true;
+            }
+            infos.push(info);
+        }
+
+        if instruction.program_id == system_program::id() {
+            process_system_instruction(&instruction.data, &infos)
+        } else if instruction.program_id == spl_token::id() {
+            spl_token::processor::Processor::process(&spl_token::id(), &infos, &instruction.data)
+        } else {
+            Err(ProgramError::IncorrectProgramId)
+        }
+    }
+}
+
+// Minimal system program: just the instructions the reward program uses.
+fn process_system_instruction(data: &[u8], infos: &[AccountInfo]) -> ProgramResult {
+    let read_u64 = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+    let tag = u32::from_le_bytes(data[..4].try_into().unwrap());
+    match tag {
+        // CreateAccount { lamports, space, owner }
+        0 => {
+            let (from, to) = (&infos[0], &infos[1]);
+            if to.lamports() > 0 || !to.data_is_empty() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            let lamports = read_u64(4);
+            let space = read_u64(12) as usize;
+            let owner = Pubkey::try_from(&data[20..52]).unwrap();
+            transfer_lamports(from, to, lamports)?;
+            to.resize(space)?;
+            to.assign(&owner);
+            Ok(())
+        }
+        // Assign { owner }
+        1 => {
+            let account = &infos[0];
+            if !account.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            account.assign(&Pubkey::try_from(&data[4..36]).unwrap());
+            Ok(())
+        }
+        // Transfer { lamports }
+        2 => transfer_lamports(&infos[0], &infos[1], read_u64(4)),
+        // Allocate { space }
+        8 => {
+            let account = &infos[0];
+            if !account.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if *account.owner != system_program::id() || !account.data_is_empty() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            account.resize(read_u64(4) as usize)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut from_lamports = from.try_borrow_mut_lamports()?;
+    **from_lamports = from_lamports
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+// Contents of the instructions sysvar for a transaction, current index unset
+fn instructions_sysvar_data(instructions: &[Instruction]) -> Vec<u8> {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+    construct_instructions_data(&borrowed)
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TestAccount {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+}
+
+// An account laid out like the runtime's serialized input, so that
+// `AccountInfo::realloc` and `AccountInfo::assign` work on the host.
+struct AccountBuffers {
+    key: &'static Pubkey,
+    lamports: &'static mut u64,
+    data: &'static mut [u8],
+    owner: &'static Pubkey,
+}
+
+fn leak_buffers(key: &Pubkey, account: &TestAccount) -> AccountBuffers {
+    // 4 bytes of original data length directly precede the key
+    let key_words: &'static mut [u32] = Box::leak(vec![0u32; 9].into_boxed_slice());
+    key_words[0] = account.data.len() as u32;
+    let key_bytes =
+        unsafe { core::slice::from_raw_parts_mut(key_words.as_mut_ptr().add(1) as *mut u8, 32) };
+    key_bytes.copy_from_slice(key.as_ref());
+    let key = unsafe { &*(key_bytes.as_ptr() as *const Pubkey) };
+
+    // 8 bytes of data length directly precede the data, followed by realloc headroom
+    let capacity = account.data.len() + MAX_PERMITTED_DATA_INCREASE;
+    let data_words: &'static mut [u64] =
+        Box::leak(vec![0u64; 1 + capacity.div_ceil(8)].into_boxed_slice());
+    data_words[0] = account.data.len() as u64;
+    let data = unsafe {
+        core::slice::from_raw_parts_mut(data_words.as_mut_ptr().add(1) as *mut u8, capacity)
+    };
+    data[..account.data.len()].copy_from_slice(&account.data);
+    let data = &mut data[..account.data.len()];
+
+    AccountBuffers {
+        key,
+        lamports: Box::leak(Box::new(account.lamports)),
+        data,
+        owner: Box::leak(Box::new(account.owner)),
+    }
+}
+
+pub struct TestEnv {
+    pub program_id: Pubkey,
+    accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        install_stubs();
+        CLOCK.with(|clock| *clock.borrow_mut() = Clock::default());
+        Self {
+            program_id: Pubkey::new_unique(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: TestAccount) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn remove_account(&mut self, key: &Pubkey) {
+        self.accounts.remove(key);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&TestAccount> {
+        self.accounts.get(key)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |account| account.lamports)
+    }
+
+    // Send a few lamports to a PDA before the program creates it, as anyone may
+    pub fn pre_fund(&mut self, key: &Pubkey) {
+        self.set_account(
+            *key,
+            TestAccount {
+                lamports: 1_000,
+                data: Vec::new(),
+                owner: system_program::id(),
+            },
+        );
+    }
+
+    // Create a system-owned wallet holding 10 SOL
+    pub fn create_wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.set_account(
+            key,
+            TestAccount {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                data: Vec::new(),
+                owner: system_program::id(),
+            },
+        );
+        key
+    }
+
+    pub fn set_clock(&self, unix_timestamp: i64) {
+        CLOCK.with(|clock| clock.borrow_mut().unix_timestamp = unix_timestamp);
+    }
+
+    pub fn set_epoch(&self, epoch: u64) {
+        CLOCK.with(|clock| clock.borrow_mut().epoch = epoch);
+    }
+
+    pub fn clock(&self) -> Clock {
+        CLOCK.with(|clock| clock.borrow().clone())
+    }
+
+    pub fn return_data(&self) -> Option<Vec<u8>> {
+        RETURN_DATA.with(|data| data.borrow().as_ref().map(|(_, data)| data.clone()))
+    }
+
+    // Fields logged with sol_log_data by the last transaction, in order
+    pub fn program_data(&self) -> Vec<Vec<u8>> {
+        PROGRAM_DATA.with(|data| data.borrow().clone())
+    }
+
+    pub fn process(&mut self, instruction: Instruction) -> ProgramResult {
+        self.process_transaction(&[instruction])
+    }
+
+    // Run every instruction addressed to the program, atomically. Instructions
+    // for other programs are skipped, including precompiles like ed25519 whose
+    // signatures the runtime would verify up front.
+    pub fn process_transaction(&mut self, instructions: &[Instruction]) -> ProgramResult {
+        PROGRAM_ID.with(|id| *id.borrow_mut() = self.program_id);
+        RETURN_DATA.with(|data| *data.borrow_mut() = None);
+        PROGRAM_DATA.with(|data| data.borrow_mut().clear());
+
+        let snapshot = self.accounts.clone();
+        let mut sysvar_data = instructions_sysvar_data(instructions);
+        for (index, instruction) in instructions.iter().enumerate() {
+            if instruction.program_id != self.program_id {
+                continue;
+            }
+            // The current index is kept in the last two bytes of the sysvar
+            let last = sysvar_data.len() - 2;
+            sysvar_data[last..].copy_from_slice(&(index as u16).to_le_bytes());
+            self.accounts.insert(
+                solana_instructions_sysvar::id(),
+                TestAccount {
+                    lamports: 1,
+                    data: sysvar_data.clone(),
+                    owner: sysvar::id(),
+                },
+            );
+            let result = self.execute(instruction);
+            self.accounts.remove(&solana_instructions_sysvar::id());
+            if let Err(err) = result {
+                self.accounts = snapshot;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> ProgramResult {
+        let mut keys: Vec<Pubkey> = Vec::new();
+        let mut infos: Vec<AccountInfo<'static>> = Vec::new();
+        let mut metas: Vec<AccountInfo<'static>> = Vec::new();
+        for meta in &instruction.accounts {
+            if let Some(position) = keys.iter().position(|key| key == &meta.pubkey) {
+                metas.push(infos[position].clone());
+                continue;
+            }
+            let account = self
+                .accounts
+                .get(&meta.pubkey)
+                .cloned()
+                .unwrap_or_else(|| TestAccount {
+                    owner: system_program::id(),
+                    ..TestAccount::default()
+                });
+            let buffers = leak_buffers(&meta.pubkey, &account);
+            let info = AccountInfo::new(
+                buffers.key,
+                meta.is_signer,
+                meta.is_writable,
+                buffers.lamports,
+                buffers.data,
+                buffers.owner,
+                false,
+                0,
+            );
+            keys.push(meta.pubkey);
+            infos.push(info.clone());
+            metas.push(info);
+        }
+
+        process_instruction(&self.program_id, &metas, &instruction.data)?;
+
+        for (key, info) in keys.iter().zip(infos.iter()) {
+            // Like the runtime, accounts left without lamports cease to exist
+            if info.lamports() == 0 {
+                self.accounts.remove(key);
+                continue;
+            }
+            self.accounts.insert(
+                *key,
+                TestAccount {
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    // --- SPL Token helpers -------------------------------------------------
+
+    pub fn create_mint(&mut self, authority: &Pubkey, decimals: u8) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: Some(*authority).into(),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        }
+        .pack_into_slice(&mut data);
+        self.set_account(
+            key,
+            TestAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: spl_token::id(),
+            },
+        );
+        key
+    }
+
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.create_token_account_at(key, mint, owner, amount);
+        key
+    }
+
+    // The mint's vault PDA, held by the vault authority PDA
+    pub fn create_vault(&mut self, mint: &Pubkey, amount: u64) -> Pubkey {
+        let (key, authority) = (self.vault_address(mint), self.vault_authority_address());
+        self.create_token_account_at(key, mint, &authority, amount);
+        key
+    }
+
+    pub fn create_token_account_at(
+        &mut self,
+        key: Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        }
+        .pack_into_slice(&mut data);
+        self.set_account(
+            key,
+            TestAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: spl_token::id(),
+            },
+        );
+        if let Some(mut mint_state) = self.mint(mint) {
+            mint_state.supply += amount;
+            mint_state.pack_into_slice(&mut self.accounts.get_mut(mint).unwrap().data);
+        }
+    }
+
+    pub fn set_mint_authority(&mut self, mint: &Pubkey, authority: &Pubkey) {
+        let mut mint_state = self.mint(mint).unwrap();
+        mint_state.mint_authority = Some(*authority).into();
+        mint_state.pack_into_slice(&mut self.accounts.get_mut(mint).unwrap().data);
+    }
+
+    pub fn mint(&self, key: &Pubkey) -> Option<spl_token::state::Mint> {
+        spl_token::state::Mint::unpack(&self.accounts.get(key)?.data).ok()
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        spl_token::state::Account::unpack(&self.accounts[key].data)
+            .unwrap()
+            .amount
+    }
+
+    // --- Reward program helpers --------------------------------------------
+
+    pub fn reward_address(&self, owner: &Pubkey) -> Pubkey {
+        pda::find_reward_address(&self.program_id, owner).0
+    }
+
+    pub fn legacy_reward_address(&self) -> Pubkey {
+        pda::find_legacy_reward_address(&self.program_id).0
+    }
+
+    pub fn config_address(&self) -> Pubkey {
+        pda::find_config_address(&self.program_id).0
+    }
+
+    pub fn program_data_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[self.program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+    }
+
+    // ProgramData account of the upgradeable loader naming `authority` as the
+    // program's upgrade authority, which InitConfig requires to sign
+    pub fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        let mut data = vec![0; 45];
+        data[..4].copy_from_slice(&3u32.to_le_bytes());
+        data[12] = 1;
+        data[13..].copy_from_slice(authority.as_ref());
+        self.set_account(
+            self.program_data_address(),
+            TestAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: bpf_loader_upgradeable::id(),
+            },
+        );
+    }
+
+    pub fn mint_authority_address(&self) -> Pubkey {
+        pda::find_mint_authority(&self.program_id).0
+    }
+
+    pub fn vault_authority_address(&self) -> Pubkey {
+        pda::find_vault_authority(&self.program_id).0
+    }
+
+    pub fn vault_address(&self, mint: &Pubkey) -> Pubkey {
+        pda::find_vault_address(&self.program_id, mint).0
+    }
+
+    pub fn scheduled_claim_address(&self, user: &Pubkey, id: u32) -> Pubkey {
+        pda::find_scheduled_claim_address(&self.program_id, user, id).0
+    }
+
+    pub fn creator_stats_address(&self, creator: &Pubkey) -> Pubkey {
+        pda::find_creator_stats_address(&self.program_id, creator).0
+    }
+
+    pub fn guardian_set_address(&self, user: &Pubkey) -> Pubkey {
+        pda::find_guardian_set_address(&self.program_id, user).0
+    }
+
+    pub fn nonce_registry_address(&self, user: &Pubkey) -> Pubkey {
+        pda::find_nonce_registry_address(&self.program_id, user).0
+    }
+
+    // Store a wallet's reward account directly, bypassing Init. The bump and owner
+    // are filled in as Init would
+    pub fn set_reward_account(&mut self, owner: &Pubkey, reward_account: &RewardAccount) {
+        let (key, bump) = pda::find_reward_address(&self.program_id, owner);
+        let mut reward_account =
+            RewardAccount::try_from_slice(&borsh::to_vec(reward_account).unwrap()).unwrap();
+        reward_account.bump = bump;
+        reward_account.owner = *owner;
+        let data = account_data(&reward_account);
+        self.set_account(
+            key,
+            TestAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: self.program_id,
+            },
+        );
+    }
+
+    // Store a program-owned reward account at a fresh address
+    pub fn create_reward_account(&mut self, reward_account: &RewardAccount) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let data = account_data(reward_account);
+        self.set_account(
+            key,
+            TestAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: self.program_id,
+            },
+        );
+        key
+    }
+
+    pub fn reward_account_at(&self, key: &Pubkey) -> RewardAccount {
+        RewardAccount::try_from_account_data(&self.accounts[key].data).unwrap()
+    }
+
+    pub fn reward_account(&self, owner: &Pubkey) -> RewardAccount {
+        self.reward_account_at(&self.reward_address(owner))
+    }
+
+    pub fn config(&self) -> Config {
+        Config::try_from_account_data(&self.accounts[&self.config_address()].data).unwrap()
+    }
+}
+
+// Account data as the program writes it: discriminator first
+pub fn account_data<T: AccountState>(state: &T) -> Vec<u8> {
+    let mut data = vec![0; DISCRIMINATOR_LEN + borsh::to_vec(state).unwrap().len()];
+    state.write_account_data(&mut data).unwrap();
+    data
+}
+
+pub fn instruction(
+    env: &TestEnv,
+    data: &RewardInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction {
+        program_id: env.program_id,
+        accounts,
+        data: borsh::to_vec(data).unwrap(),
+    }
+}
+
+// Ed25519 program instruction carrying `signature` over `message` by `signer`.
+// The harness, like the runtime, checks precompiles before the program runs,
+// so the signature bytes themselves are not inspected here.
+pub fn ed25519_instruction(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    const DATA_START: u16 = 16;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+    let offsets = [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ];
+
+    let mut data = vec![1, 0];
+    data.extend(offsets.iter().flat_map(|offset| offset.to_le_bytes()));
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+pub const EXCHANGE_RATE: u64 = 1_000;
+pub const VAULT_BALANCE: u64 = 1_000_000;
+// Backend key the default config requires on every Earn
+pub const EARN_AUTHORITY: Pubkey = Pubkey::new_from_array([0xea; 32]);
+
+pub fn default_params() -> ConfigParams {
+    ConfigParams {
+        exchange_rate: EXCHANGE_RATE,
+        max_vault_fraction_bps: BPS_DENOMINATOR,
+        earn_authority: EARN_AUTHORITY,
+        ..ConfigParams::default()
+    }
+}
+
+// A configured program with a funded vault and one user holding points
+pub struct Fixture {
+    pub env: TestEnv,
+    pub signer: Pubkey,
+    pub user_token: Pubkey,
+    pub vault_token: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Fixture {
+    pub fn new(total_points: u64) -> Self {
+        Self::with_params(total_points, default_params())
+    }
+
+    pub fn with_params(total_points: u64, params: ConfigParams) -> Self {
+        let authority = params.earn_authority;
+        let mut fixture = Self::configured(params);
+        let mint = fixture.mint;
+        fixture.set_reward_account(&RewardAccount {
+            total_points,
+            mint,
+            authority,
+            ..RewardAccount::default()
+        });
+        fixture
+    }
+
+    // Config and token accounts only, without a reward account
+    pub fn configured(params: ConfigParams) -> Self {
+        let mut env = TestEnv::new();
+        let signer = env.create_wallet();
+        env.set_upgrade_authority(&signer);
+        let mint = env.create_mint(&signer, 6);
+        let user_token = env.create_token_account(&mint, &signer, 0);
+        // Claims are paid out of the mint's vault PDA by the vault authority PDA
+        let vault_token = env.create_vault(&mint, VAULT_BALANCE);
+        let mut fixture = Self {
+            env,
+            signer,
+            user_token,
+            vault_token,
+            mint,
+        };
+
+        let init_config = fixture.instruction(RewardInstruction::InitConfig { params });
+        fixture.env.process(init_config).unwrap();
+        fixture
+    }
+
+    // The signer's reward account
+    pub fn reward_address(&self) -> Pubkey {
+        self.env.reward_address(&self.signer)
+    }
+
+    pub fn reward_account(&self) -> RewardAccount {
+        self.env.reward_account(&self.signer)
+    }
+
+    pub fn set_reward_account(&mut self, reward_account: &RewardAccount) {
+        let signer = self.signer;
+        self.env.set_reward_account(&signer, reward_account);
+    }
+
+    // The accounts the processor expects for `data`, up to the config PDA
+    pub fn instruction(&self, data: RewardInstruction) -> Instruction {
+        self.instruction_signed_by(&self.signer, data)
+    }
+
+    // Same as `instruction`, but signed by another wallet. The signer's own reward
+    // PDA stands in for the user's in AdjustLifetime, RecoverOwnership and SetPaused
+    pub fn instruction_signed_by(&self, signer: &Pubkey, data: RewardInstruction) -> Instruction {
+        let reward = AccountMeta::new(self.env.reward_address(signer), false);
+        let user_token = AccountMeta::new(self.user_token, false);
+        let vault = AccountMeta::new(self.vault_token, false);
+        let mint = AccountMeta::new_readonly(self.mint, false);
+        let token_program = AccountMeta::new_readonly(spl_token::id(), false);
+        let system = AccountMeta::new_readonly(system_program::id(), false);
+        let mut accounts = vec![AccountMeta::new(*signer, true)];
+        accounts.extend(match data {
+            RewardInstruction::Init { .. } => vec![reward, mint, system],
+            RewardInstruction::Claim { .. } | RewardInstruction::WithdrawStream => {
+                vec![reward, user_token, vault, mint, token_program]
+            }
+            RewardInstruction::ClaimSigned { .. } => {
+                vec![reward, user_token, vault, mint, token_program, system]
+            }
+            RewardInstruction::ScheduleClaim { .. } | RewardInstruction::OpenStream { .. } => {
+                vec![reward, user_token, vault, system]
+            }
+            RewardInstruction::ExecuteScheduledClaim => {
+                vec![user_token, vault, mint, token_program]
+            }
+            RewardInstruction::CancelStream | RewardInstruction::RescueTokens { .. } => {
+                vec![user_token, vault, token_program]
+            }
+            RewardInstruction::CanPerform { .. } | RewardInstruction::GetFullStatus => {
+                vec![reward, vault]
+            }
+            RewardInstruction::MintToken { .. } | RewardInstruction::Burn { .. } => {
+                vec![vault, mint, token_program]
+            }
+            RewardInstruction::InitVault => vec![vault, mint, token_program, system],
+            RewardInstruction::MigrateLegacyLayout => vec![reward, mint, system],
+            RewardInstruction::MigrateGlobalAccount => {
+                let legacy = AccountMeta::new(self.env.legacy_reward_address(), false);
+                vec![reward, legacy, mint, system]
+            }
+            RewardInstruction::Earn { .. }
+            | RewardInstruction::SetNote { .. }
+            | RewardInstruction::EarnWithVoucher { .. } => vec![reward, system],
+            RewardInstruction::InitConfig { .. }
+            | RewardInstruction::SetGuardians { .. }
+            | RewardInstruction::SetReferralCode { .. } => vec![system],
+            RewardInstruction::ClaimAndStake { .. }
+            | RewardInstruction::AdjustLifetime { .. }
+            | RewardInstruction::CancelScheduledClaim
+            | RewardInstruction::CloseReward { .. }
+            | RewardInstruction::RecoverOwnership { .. }
+            | RewardInstruction::SetPaused { .. }
+            | RewardInstruction::PoolPoints { .. }
+            | RewardInstruction::MergeAccounts => vec![reward],
+            _ => vec![],
+        });
+        accounts.push(AccountMeta::new(self.env.config_address(), false));
+        if matches!(data, RewardInstruction::InitConfig { .. }) {
+            accounts.extend([
+                AccountMeta::new_readonly(self.env.program_data_address(), false),
+                AccountMeta::new_readonly(self.mint, false),
+            ]);
+        }
+        // Init only reads the vault when new users must wait for it to be funded
+        if matches!(data, RewardInstruction::Init { .. })
+            && self.env.config().params.require_funded_vault
+        {
+            accounts.push(AccountMeta::new_readonly(self.vault_token, false));
+        }
+        if matches!(
+            data,
+            RewardInstruction::Claim { .. } | RewardInstruction::ClaimSigned { .. }
+        ) {
+            accounts.push(AccountMeta::new_readonly(
+                self.env.vault_authority_address(),
+                false,
+            ));
+        }
+        if matches!(data, RewardInstruction::MintToken { .. }) {
+            accounts.push(AccountMeta::new_readonly(
+                self.env.mint_authority_address(),
+                false,
+            ));
+        }
+        instruction(&self.env, &data, accounts)
+    }
+
+    // Earn signed by `signer`, with `authority` co-signing after the config
+    pub fn earn_instruction(
+        &self,
+        signer: &Pubkey,
+        authority: &Pubkey,
+        points: u32,
+    ) -> Instruction {
+        let mut ix = self.instruction_signed_by(signer, RewardInstruction::Earn { points });
+        ix.accounts
+            .push(AccountMeta::new_readonly(*authority, true));
+        ix
+    }
+
+    // Earn for the fixture signer, co-signed by the default earn authority
+    pub fn earn(&mut self, points: u32) -> ProgramResult {
+        let ix = self.earn_instruction(&self.signer, &EARN_AUTHORITY, points);
+        self.env.process(ix)
+    }
+
+    pub fn process(&mut self, data: RewardInstruction) -> ProgramResult {
+        let ix = self.instruction(data);
+        self.env.process(ix)
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{default_params, Fixture, TestAccount};
+use solana_program::{hash::hash, program_error::ProgramError, rent::Rent};
+use wagus_reward_system::{
+    AccountState, Config, CreatorStats, GuardianSet, NonceRegistry, ReferralCode, RewardAccount,
+    RewardError, RewardInstruction, RewardStream, ScheduledClaim, DISCRIMINATOR_LEN,
+};
+
+fn anchor_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    hash(format!("account:{name}").as_bytes()).to_bytes()[..DISCRIMINATOR_LEN]
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn discriminators_follow_the_anchor_derivation() {
+    let discriminators = [
+        ("RewardAccount", RewardAccount::DISCRIMINATOR),
+        ("ScheduledClaim", ScheduledClaim::DISCRIMINATOR),
+        ("RewardStream", RewardStream::DISCRIMINATOR),
+        ("GuardianSet", GuardianSet::DISCRIMINATOR),
+        ("CreatorStats", CreatorStats::DISCRIMINATOR),
+        ("NonceRegistry", NonceRegistry::DISCRIMINATOR),
+        ("ReferralCode", ReferralCode::DISCRIMINATOR),
+        ("Config", Config::DISCRIMINATOR),
+    ];
+    for (name, discriminator) in discriminators {
+        assert_eq!(discriminator, anchor_discriminator(name), "{name}");
+    }
+}
+
+#[test]
+fn init_writes_discriminated_accounts() {
+    let mut fixture = Fixture::configured(default_params());
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    let reward_address = fixture.reward_address();
+    let reward_data = &fixture.env.account(&reward_address).unwrap().data;
+    assert_eq!(
+        reward_data[..DISCRIMINATOR_LEN],
+        RewardAccount::DISCRIMINATOR
+    );
+    let config_address = fixture.env.config_address();
+    let config_data = &fixture.env.account(&config_address).unwrap().data;
+    assert_eq!(config_data[..DISCRIMINATOR_LEN], Config::DISCRIMINATOR);
+}
+
+#[test]
+fn reward_account_with_another_discriminator_is_rejected() {
+    let mut fixture = Fixture::new(40);
+    let reward_address = fixture.reward_address();
+    let mut account = fixture.env.account(&reward_address).unwrap().clone();
+    account.data[..DISCRIMINATOR_LEN].copy_from_slice(&Config::DISCRIMINATOR);
+    fixture.env.set_account(reward_address, account);
+
+    assert_eq!(fixture.earn(5), Err(RewardError::InvalidPda.into()));
+}
+
+#[test]
+fn reward_account_passed_as_the_config_is_rejected() {
+    let mut fixture = Fixture::new(40);
+    let reward_address = fixture.reward_address();
+    let data = fixture.env.account(&reward_address).unwrap().data.clone();
+    let config_address = fixture.env.config_address();
+    let owner = fixture.env.program_id;
+    fixture.env.set_account(
+        config_address,
+        TestAccount {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+        },
+    );
+
+    assert_eq!(fixture.earn(5), Err(ProgramError::InvalidAccountData));
+}
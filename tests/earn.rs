@@ -0,0 +1,200 @@
+mod common;
+
+use common::{default_params, Fixture, EARN_AUTHORITY};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction};
+
+fn min_earn_fixture(min_recordable_earn: u32) -> Fixture {
+    Fixture::with_params(
+        0,
+        ConfigParams {
+            min_recordable_earn,
+            ..default_params()
+        },
+    )
+}
+
+#[test]
+fn earn_below_minimum_is_not_credited() {
+    let mut fixture = min_earn_fixture(10);
+
+    fixture.earn(4).unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 0);
+    assert_eq!(reward_account.pending_points, 4);
+}
+
+#[test]
+fn earns_below_minimum_accumulate_until_recorded() {
+    let mut fixture = min_earn_fixture(10);
+
+    for points in [4, 3] {
+        fixture.earn(points).unwrap();
+    }
+    assert_eq!(fixture.reward_account().total_points, 0);
+    assert_eq!(fixture.reward_account().pending_points, 7);
+
+    fixture.earn(5).unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 12);
+    assert_eq!(reward_account.pending_points, 0);
+}
+
+#[test]
+fn earn_at_minimum_is_credited_immediately() {
+    let mut fixture = min_earn_fixture(10);
+
+    fixture.earn(10).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 10);
+}
+
+#[test]
+fn signers_accumulate_independent_balances() {
+    let mut fixture = Fixture::configured(default_params());
+    let other = fixture.env.create_wallet();
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+    let init_other = fixture.instruction_signed_by(
+        &other,
+        RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        },
+    );
+    fixture.env.process(init_other).unwrap();
+
+    fixture.earn(30).unwrap();
+    let earn_other = fixture.earn_instruction(&other, &EARN_AUTHORITY, 5);
+    fixture.env.process(earn_other).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 30);
+    assert_eq!(fixture.env.reward_account(&other).total_points, 5);
+}
+
+#[test]
+fn earn_rejects_another_wallets_reward_account() {
+    let mut fixture = Fixture::new(0);
+    let other = fixture.env.create_wallet();
+
+    // Signed by `other` but pointing at the fixture signer's account
+    let mut ix = fixture.earn_instruction(&other, &EARN_AUTHORITY, 5);
+    ix.accounts[1].pubkey = fixture.reward_address();
+
+    assert_eq!(fixture.env.process(ix), Err(RewardError::InvalidPda.into()));
+    assert_eq!(fixture.reward_account().total_points, 0);
+}
+
+#[test]
+fn earn_requires_the_earn_authority() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = fixture.earn_instruction(&fixture.signer, &intruder, 5);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+}
+
+#[test]
+fn earn_without_the_authority_account_is_rejected() {
+    let mut fixture = Fixture::new(0);
+
+    let mut ix = fixture.earn_instruction(&fixture.signer, &EARN_AUTHORITY, 5);
+    ix.accounts.pop();
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::MissingAccount.into())
+    );
+}
+
+#[test]
+fn users_cannot_grant_themselves_points() {
+    let mut fixture = Fixture::configured(default_params());
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+    assert_eq!(fixture.reward_account().authority, EARN_AUTHORITY);
+
+    // Co-signing as their own authority is not enough
+    let ix = fixture.earn_instruction(&fixture.signer, &fixture.signer, 5);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+
+    // Naming the earn authority without its signature is not enough either
+    let mut ix = fixture.earn_instruction(&fixture.signer, &EARN_AUTHORITY, 5);
+    ix.accounts.last_mut().unwrap().is_signer = false;
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+
+    fixture.earn(5).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 5);
+}
+
+#[test]
+fn config_without_an_earn_authority_is_rejected() {
+    let mut fixture = Fixture::new(0);
+    let params = ConfigParams {
+        earn_authority: Pubkey::default(),
+        ..default_params()
+    };
+
+    assert_eq!(
+        fixture.process(RewardInstruction::UpdateConfig { params }),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+const COOLDOWN_SECS: u32 = 60;
+
+#[test]
+fn earn_within_the_cooldown_is_rejected() {
+    let mut fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            earn_cooldown_secs: COOLDOWN_SECS,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    fixture.earn(10).unwrap();
+
+    fixture.env.set_clock(1_000 + COOLDOWN_SECS as i64 - 1);
+    assert_eq!(fixture.earn(10), Err(RewardError::Cooldown.into()));
+    assert_eq!(fixture.reward_account().total_points, 10);
+
+    fixture.env.set_clock(1_000 + COOLDOWN_SECS as i64);
+    fixture.earn(10).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 20);
+    assert_eq!(reward_account.last_earn_ts, 1_000 + COOLDOWN_SECS as i64);
+}
+
+#[test]
+fn earn_on_a_current_account_needs_no_system_program() {
+    let mut fixture = Fixture::new(0);
+
+    let mut ix = fixture.earn_instruction(&fixture.signer, &EARN_AUTHORITY, 25);
+    ix.accounts.remove(2);
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 25);
+}
@@ -0,0 +1,350 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{default_params, ed25519_instruction, Fixture, VAULT_BALANCE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+};
+use wagus_reward_system::{
+    kyc_attestation_message, BlockReason, ConfigParams, Eligibility, RewardAccount,
+    RewardInstruction, ACTION_CLAIM, ACTION_EARN, ACTION_INIT, MEMO_PROGRAM_ID,
+};
+
+fn can_perform(fixture: &mut Fixture, action: u8) -> Eligibility {
+    can_perform_in(fixture, action, vec![], vec![])
+}
+
+// CanPerform with `extra` accounts after the config, run after the `preceding` instructions
+fn can_perform_in(
+    fixture: &mut Fixture,
+    action: u8,
+    preceding: Vec<Instruction>,
+    extra: Vec<AccountMeta>,
+) -> Eligibility {
+    let reward_data = |fixture: &Fixture| {
+        let reward_address = fixture.reward_address();
+        fixture
+            .env
+            .account(&reward_address)
+            .map(|account| account.data.clone())
+    };
+    let data_before = reward_data(fixture);
+    let mut ix = fixture.instruction(RewardInstruction::CanPerform { action });
+    ix.accounts.extend(extra);
+    let mut instructions = preceding;
+    instructions.push(ix);
+    fixture.env.process_transaction(&instructions).unwrap();
+    // The check never writes to the reward account
+    assert_eq!(reward_data(fixture), data_before);
+    Eligibility::try_from_slice(&fixture.env.return_data().unwrap()).unwrap()
+}
+
+fn blocked(reason: BlockReason) -> Eligibility {
+    Eligibility {
+        allowed: false,
+        reason,
+    }
+}
+
+const ALLOWED: Eligibility = Eligibility {
+    allowed: true,
+    reason: BlockReason::None,
+};
+
+#[test]
+fn init_is_allowed_once() {
+    let mut fixture = Fixture::configured(default_params());
+    assert_eq!(can_perform(&mut fixture, ACTION_INIT), ALLOWED);
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_INIT),
+        blocked(BlockReason::AlreadyInitialized)
+    );
+}
+
+#[test]
+fn init_is_blocked_by_an_underfunded_vault() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        require_funded_vault: true,
+        min_vault_balance: VAULT_BALANCE + 1,
+        ..default_params()
+    });
+
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_INIT),
+        blocked(BlockReason::VaultNotFunded)
+    );
+}
+
+#[test]
+fn earn_and_claim_require_a_reward_account() {
+    let mut fixture = Fixture::configured(default_params());
+
+    for action in [ACTION_EARN, ACTION_CLAIM] {
+        assert_eq!(
+            can_perform(&mut fixture, action),
+            blocked(BlockReason::NotInitialized)
+        );
+    }
+}
+
+#[test]
+fn claim_needs_points_beyond_the_fee() {
+    let params = ConfigParams {
+        claim_point_fee: 5,
+        ..default_params()
+    };
+
+    let mut fixture = Fixture::with_params(5, params.clone());
+    assert_eq!(can_perform(&mut fixture, ACTION_EARN), ALLOWED);
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_CLAIM),
+        blocked(BlockReason::InsufficientPoints)
+    );
+
+    let mut fixture = Fixture::with_params(6, params);
+    assert_eq!(can_perform(&mut fixture, ACTION_CLAIM), ALLOWED);
+}
+
+#[test]
+fn unknown_action_is_rejected() {
+    let mut fixture = Fixture::new(0);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::CanPerform { action: 200 }),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn paused_accounts_can_neither_earn_nor_claim() {
+    let mut fixture = Fixture::new(100);
+    fixture.set_reward_account(&RewardAccount {
+        paused: true,
+        ..fixture.reward_account()
+    });
+
+    for action in [ACTION_EARN, ACTION_CLAIM] {
+        assert_eq!(
+            can_perform(&mut fixture, action),
+            blocked(BlockReason::AccountPaused)
+        );
+    }
+}
+
+#[test]
+fn earn_is_blocked_during_its_cooldown() {
+    let mut fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            earn_cooldown_secs: 60,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    fixture.earn(10).unwrap();
+
+    fixture.env.set_clock(1_059);
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_EARN),
+        blocked(BlockReason::Cooldown)
+    );
+    fixture.env.set_clock(1_060);
+    assert_eq!(can_perform(&mut fixture, ACTION_EARN), ALLOWED);
+}
+
+#[test]
+fn earn_is_blocked_by_an_empty_bucket() {
+    let mut fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            earn_bucket_capacity: 10,
+            earn_refill_per_sec: 1,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    fixture.earn(10).unwrap();
+
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_EARN),
+        blocked(BlockReason::RateLimited)
+    );
+    fixture.env.set_clock(1_001);
+    assert_eq!(can_perform(&mut fixture, ACTION_EARN), ALLOWED);
+}
+
+#[test]
+fn claim_is_blocked_during_its_cooldown() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            claim_cooldown_secs: 60,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 10,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    fixture.env.set_clock(1_059);
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_CLAIM),
+        blocked(BlockReason::ClaimCooldown)
+    );
+    fixture.env.set_clock(1_060);
+    assert_eq!(can_perform(&mut fixture, ACTION_CLAIM), ALLOWED);
+}
+
+#[test]
+fn claim_is_blocked_for_the_rest_of_the_epoch() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            one_claim_per_epoch: true,
+            ..default_params()
+        },
+    );
+    fixture.env.set_epoch(0);
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 10,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_CLAIM),
+        blocked(BlockReason::AlreadyClaimedThisEpoch)
+    );
+    fixture.env.set_epoch(1);
+    assert_eq!(can_perform(&mut fixture, ACTION_CLAIM), ALLOWED);
+}
+
+#[test]
+fn init_is_blocked_by_the_signup_cap() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_total_users: 1,
+        ..default_params()
+    });
+    assert_eq!(can_perform(&mut fixture, ACTION_INIT), ALLOWED);
+
+    let other = fixture.env.create_wallet();
+    let init = fixture.instruction_signed_by(
+        &other,
+        RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        },
+    );
+    fixture.env.process(init).unwrap();
+    assert_eq!(
+        can_perform(&mut fixture, ACTION_INIT),
+        blocked(BlockReason::SignupCapReached)
+    );
+}
+
+#[test]
+fn init_is_blocked_by_the_creation_cap() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_accounts_per_creator: 1,
+        ..default_params()
+    });
+    let stats = vec![AccountMeta::new_readonly(
+        fixture.env.creator_stats_address(&fixture.signer),
+        false,
+    )];
+    assert_eq!(
+        can_perform_in(&mut fixture, ACTION_INIT, vec![], stats.clone()),
+        ALLOWED
+    );
+
+    let mut init = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    init.accounts.push(AccountMeta::new(stats[0].pubkey, false));
+    fixture.env.process(init).unwrap();
+    // Closing the account does not give the creation back
+    fixture.env.remove_account(&fixture.reward_address());
+    assert_eq!(
+        can_perform_in(&mut fixture, ACTION_INIT, vec![], stats),
+        blocked(BlockReason::CreationCapReached)
+    );
+
+    // The cap counts the rent payer, so a relayer that paid for nothing yet may sponsor it
+    let relayer = fixture.env.create_wallet();
+    let sponsored = vec![
+        AccountMeta::new_readonly(fixture.env.creator_stats_address(&relayer), false),
+        AccountMeta::new_readonly(relayer, false),
+    ];
+    assert_eq!(
+        can_perform_in(&mut fixture, ACTION_INIT, vec![], sponsored),
+        ALLOWED
+    );
+}
+
+#[test]
+fn claim_checks_the_kyc_attestation_and_memo_of_its_transaction() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            require_kyc: true,
+            require_memo: true,
+            ..default_params()
+        },
+    );
+    let sysvar = || {
+        vec![AccountMeta::new_readonly(
+            solana_instructions_sysvar::id(),
+            false,
+        )]
+    };
+    let attestation = ed25519_instruction(
+        &fixture.signer,
+        &[7; 64],
+        &kyc_attestation_message(&fixture.signer),
+    );
+    let memo = Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: b"order 42".to_vec(),
+    };
+
+    assert_eq!(
+        can_perform_in(&mut fixture, ACTION_CLAIM, vec![memo.clone()], sysvar()),
+        blocked(BlockReason::KycRequired)
+    );
+    assert_eq!(
+        can_perform_in(
+            &mut fixture,
+            ACTION_CLAIM,
+            vec![attestation.clone()],
+            sysvar()
+        ),
+        blocked(BlockReason::MemoRequired)
+    );
+    assert_eq!(
+        can_perform_in(
+            &mut fixture,
+            ACTION_CLAIM,
+            vec![attestation, memo],
+            sysvar()
+        ),
+        ALLOWED
+    );
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{default_params, Fixture, EXCHANGE_RATE};
+use solana_program::pubkey::Pubkey;
+use wagus_reward_system::{
+    binary_event_line, ConfigParams, RewardError, RewardEvent, RewardInstruction,
+    BINARY_EVENT_PREFIX,
+};
+
+fn decode_base64(encoded: &str) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bytes = Vec::new();
+    let mut word = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes().take_while(|&c| c != b'=') {
+        let value = ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+        word = word << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((word >> bits) as u8);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn binary_event_line_decodes_back_to_the_event() {
+    let event = RewardEvent::Claimed {
+        user: Pubkey::new_unique(),
+        required_points: 40,
+        amount: 40_000,
+        decimals: 6,
+    };
+
+    let line = binary_event_line(&event).unwrap();
+    let payload = line.strip_prefix(BINARY_EVENT_PREFIX).unwrap();
+    assert!(!payload.contains(char::is_whitespace));
+
+    let bytes = decode_base64(payload);
+    // Variant index comes first, followed by the Borsh-encoded fields
+    assert_eq!(bytes[0], 1);
+    assert_eq!(RewardEvent::try_from_slice(&bytes).unwrap(), event);
+}
+
+#[test]
+fn binary_events_replace_the_program_data_encoding() {
+    let mut fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            binary_events: true,
+            ..default_params()
+        },
+    );
+
+    fixture.earn(25).unwrap();
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 10,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 15);
+    // Events go out as WGEV lines only, never also as program data
+    assert!(fixture.env.program_data().is_empty());
+}
+
+fn logged_events(fixture: &Fixture) -> Vec<RewardEvent> {
+    fixture
+        .env
+        .program_data()
+        .iter()
+        .map(|data| RewardEvent::try_from_slice(data).unwrap())
+        .collect()
+}
+
+#[test]
+fn handlers_log_events_as_program_data() {
+    let mut fixture = Fixture::new(0);
+    let user = fixture.signer;
+
+    fixture.earn(100).unwrap();
+    assert_eq!(
+        logged_events(&fixture),
+        [RewardEvent::Earned {
+            user,
+            points: 100,
+            new_total: 100,
+        }]
+    );
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        })
+        .unwrap();
+    assert_eq!(
+        logged_events(&fixture),
+        [RewardEvent::Claimed {
+            user,
+            required_points: 40,
+            amount: 40 * EXCHANGE_RATE,
+            decimals: 6,
+        }]
+    );
+}
+
+#[test]
+fn claim_event_carries_the_mint_decimals() {
+    let mut fixture = Fixture::new(100);
+    let decimals = fixture.env.mint(&fixture.mint).unwrap().decimals;
+
+    fixture
+        .process(RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        })
+        .unwrap();
+
+    let [RewardEvent::Claimed {
+        amount,
+        decimals: logged,
+        ..
+    }] = logged_events(&fixture)[..]
+    else {
+        panic!("expected a single Claimed event");
+    };
+    assert_eq!(logged, decimals);
+    assert_eq!(amount, 40 * EXCHANGE_RATE);
+}
+
+#[test]
+fn claim_rejects_a_mint_other_than_the_reward_mint() {
+    let mut fixture = Fixture::new(100);
+    let other_mint = fixture.env.create_mint(&fixture.signer, 9);
+
+    let mut ix = fixture.instruction(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+    ix.accounts[4].pubkey = other_mint;
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE};
+use solana_program::program_error::ProgramError;
+use wagus_reward_system::{ConfigParams, RewardInstruction};
+
+const TIMELOCK_SECS: u32 = 3_600;
+const NOW: i64 = 1_000;
+const EFFECTIVE_TS: i64 = NOW + TIMELOCK_SECS as i64;
+
+fn timelocked_fixture() -> Fixture {
+    let fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            rate_change_timelock_secs: TIMELOCK_SECS,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(NOW);
+    fixture
+}
+
+fn claim_ten(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Claim {
+        required_points: 10,
+        expected_total_points: None,
+    })
+}
+
+#[test]
+fn claims_use_the_new_rate_only_once_it_takes_effect() {
+    let mut fixture = timelocked_fixture();
+    fixture
+        .process(RewardInstruction::SetExchangeRate {
+            rate: 2 * EXCHANGE_RATE,
+            effective_ts: EFFECTIVE_TS,
+        })
+        .unwrap();
+    let config = fixture.env.config();
+    assert_eq!(config.params.exchange_rate, EXCHANGE_RATE);
+    assert_eq!(config.pending_exchange_rate, 2 * EXCHANGE_RATE);
+
+    fixture.env.set_clock(EFFECTIVE_TS - 1);
+    claim_ten(&mut fixture).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        10 * EXCHANGE_RATE
+    );
+
+    fixture.env.set_clock(EFFECTIVE_TS);
+    claim_ten(&mut fixture).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        30 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn rate_change_inside_the_timelock_is_rejected() {
+    let mut fixture = timelocked_fixture();
+
+    let result = fixture.process(RewardInstruction::SetExchangeRate {
+        rate: EXCHANGE_RATE / 2,
+        effective_ts: EFFECTIVE_TS - 1,
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.env.config().pending_exchange_rate, 0);
+}
+
+#[test]
+fn cancelled_rate_never_takes_effect() {
+    let mut fixture = timelocked_fixture();
+    fixture
+        .process(RewardInstruction::SetExchangeRate {
+            rate: EXCHANGE_RATE / 2,
+            effective_ts: EFFECTIVE_TS,
+        })
+        .unwrap();
+    fixture
+        .process(RewardInstruction::CancelPendingRate)
+        .unwrap();
+    assert_eq!(
+        fixture.process(RewardInstruction::CancelPendingRate),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    fixture.env.set_clock(EFFECTIVE_TS);
+    claim_ten(&mut fixture).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        10 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn rate_in_effect_cannot_be_cancelled() {
+    let mut fixture = timelocked_fixture();
+    fixture
+        .process(RewardInstruction::SetExchangeRate {
+            rate: 2 * EXCHANGE_RATE,
+            effective_ts: EFFECTIVE_TS,
+        })
+        .unwrap();
+
+    fixture.env.set_clock(EFFECTIVE_TS);
+    assert_eq!(
+        fixture.process(RewardInstruction::CancelPendingRate),
+        Err(ProgramError::InvalidArgument)
+    );
+    claim_ten(&mut fixture).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        20 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn update_config_cannot_shorten_the_timelock() {
+    let mut fixture = timelocked_fixture();
+
+    // Dropping the timelock first would let an immediate rate change through
+    let result = fixture.process(RewardInstruction::UpdateConfig {
+        params: ConfigParams {
+            rate_change_timelock_secs: 0,
+            ..default_params()
+        },
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    let result = fixture.process(RewardInstruction::SetExchangeRate {
+        rate: EXCHANGE_RATE / 2,
+        effective_ts: NOW,
+    });
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    assert_eq!(
+        fixture.env.config().params.rate_change_timelock_secs,
+        TIMELOCK_SECS
+    );
+
+    // Lengthening it is fine
+    fixture
+        .process(RewardInstruction::UpdateConfig {
+            params: ConfigParams {
+                rate_change_timelock_secs: 2 * TIMELOCK_SECS,
+                ..default_params()
+            },
+        })
+        .unwrap();
+    assert_eq!(
+        fixture.env.config().params.rate_change_timelock_secs,
+        2 * TIMELOCK_SECS
+    );
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{default_params, Fixture};
+use solana_program::program_error::ProgramError;
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction};
+
+const TIMEOUT_SECS: u32 = 86_400;
+
+// Config created at time zero, so the admin's heartbeat lapses at TIMEOUT_SECS
+fn heartbeat_fixture() -> Fixture {
+    Fixture::with_params(
+        100,
+        ConfigParams {
+            heartbeat_timeout_secs: TIMEOUT_SECS,
+            ..default_params()
+        },
+    )
+}
+
+fn trigger_pause(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    let bystander = fixture.env.create_wallet();
+    let ix = fixture.instruction_signed_by(&bystander, RewardInstruction::TriggerDeadManPause);
+    fixture.env.process(ix)
+}
+
+#[test]
+fn anyone_can_pause_after_the_heartbeat_lapses() {
+    let mut fixture = heartbeat_fixture();
+    fixture.env.set_clock(TIMEOUT_SECS as i64);
+
+    trigger_pause(&mut fixture).unwrap();
+    assert!(fixture.env.config().dead_man_paused);
+    assert_eq!(fixture.earn(10), Err(RewardError::ProgramPaused.into()));
+
+    // The admin coming back lifts the pause
+    fixture.process(RewardInstruction::Heartbeat).unwrap();
+    assert!(!fixture.env.config().dead_man_paused);
+    fixture.earn(10).unwrap();
+}
+
+#[test]
+fn pause_is_rejected_while_the_heartbeat_is_fresh() {
+    let mut fixture = heartbeat_fixture();
+    fixture.env.set_clock(TIMEOUT_SECS as i64 - 1);
+    assert_eq!(
+        trigger_pause(&mut fixture),
+        Err(RewardError::HeartbeatNotExpired.into())
+    );
+
+    // A heartbeat restarts the timeout
+    fixture.process(RewardInstruction::Heartbeat).unwrap();
+    fixture.env.set_clock(2 * TIMEOUT_SECS as i64 - 2);
+    assert_eq!(
+        trigger_pause(&mut fixture),
+        Err(RewardError::HeartbeatNotExpired.into())
+    );
+    assert!(!fixture.env.config().dead_man_paused);
+}
+
+#[test]
+fn switch_is_off_without_a_timeout() {
+    let mut fixture = Fixture::new(100);
+    fixture.env.set_clock(i64::MAX);
+    assert_eq!(
+        trigger_pause(&mut fixture),
+        Err(RewardError::HeartbeatNotExpired.into())
+    );
+}
@@ -0,0 +1,548 @@
+mod common;
+
+use common::{default_params, Fixture, EARN_AUTHORITY, VAULT_BALANCE};
+use solana_program::{
+    instruction::AccountMeta, program_error::ProgramError, pubkey::Pubkey, rent::Rent,
+    system_program,
+};
+use wagus_reward_system::{
+    find_config_address, find_mint_authority, find_referral_address, find_reward_address,
+    find_vault_authority, AccountState, ConfigParams, CreatorStats, RewardError, RewardInstruction,
+    REWARD_ACCOUNT_SIZE,
+};
+
+fn funded_vault_fixture(min_vault_balance: u64) -> Fixture {
+    Fixture::configured(ConfigParams {
+        require_funded_vault: true,
+        min_vault_balance,
+        ..default_params()
+    })
+}
+
+#[test]
+fn init_creates_the_reward_account() {
+    let mut fixture = Fixture::configured(default_params());
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    let reward_address = fixture.reward_address();
+    let account = fixture.env.account(&reward_address).unwrap();
+    assert_eq!(account.owner, fixture.env.program_id);
+    assert_eq!(account.data.len(), REWARD_ACCOUNT_SIZE);
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 0);
+    assert_eq!(reward_account.mint, fixture.mint);
+    assert_eq!(reward_account.owner, fixture.signer);
+    let (_, bump) = find_reward_address(&fixture.env.program_id, &fixture.signer);
+    assert_eq!(reward_account.bump, bump);
+
+    // The config carries the bumps of the program's PDAs
+    let program_id = fixture.env.program_id;
+    let config = fixture.env.config();
+    assert_eq!(config.bump, find_config_address(&program_id).1);
+    assert_eq!(
+        config.vault_authority_bump,
+        find_vault_authority(&program_id).1
+    );
+    assert_eq!(
+        config.mint_authority_bump,
+        find_mint_authority(&program_id).1
+    );
+}
+
+#[test]
+fn init_needs_no_token_accounts() {
+    let mut fixture = Fixture::configured(default_params());
+
+    let ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        [
+            fixture.signer,
+            fixture.reward_address(),
+            fixture.mint,
+            system_program::id(),
+            fixture.env.config_address(),
+        ]
+    );
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.reward_account().mint, fixture.mint);
+}
+
+#[test]
+fn init_names_a_missing_account() {
+    let mut fixture = Fixture::configured(default_params());
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.pop();
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::MissingAccount.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+#[test]
+fn creating_the_account_needs_the_system_program() {
+    let mut fixture = Fixture::configured(default_params());
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.remove(3);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::MissingAccount.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+#[test]
+fn init_rejects_a_mint_other_than_the_configs() {
+    let mut fixture = Fixture::configured(default_params());
+    let foreign_mint = fixture.env.create_mint(&fixture.signer, 6);
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts[2].pubkey = foreign_mint;
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+#[test]
+fn init_rejects_an_existing_account() {
+    let mut fixture = Fixture::configured(default_params());
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    assert_eq!(
+        fixture.process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        }),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+}
+
+#[test]
+fn init_accepts_a_funded_vault() {
+    let mut fixture = funded_vault_fixture(VAULT_BALANCE);
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    assert!(fixture.env.account(&fixture.reward_address()).is_some());
+}
+
+#[test]
+fn init_rejects_an_underfunded_vault() {
+    let mut fixture = funded_vault_fixture(VAULT_BALANCE + 1);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        }),
+        Err(RewardError::VaultNotFunded.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+#[test]
+fn init_only_counts_the_vault_pda() {
+    let mut fixture = funded_vault_fixture(VAULT_BALANCE);
+    let impostor = fixture
+        .env
+        .create_token_account(&fixture.mint, &fixture.signer, VAULT_BALANCE);
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.last_mut().unwrap().pubkey = impostor;
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::InvalidVault.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+}
+
+// Init followed by the signer's creator stats PDA
+fn capped_init(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.push(AccountMeta::new(
+        fixture.env.creator_stats_address(&fixture.signer),
+        false,
+    ));
+    fixture.env.process(ix)
+}
+
+#[test]
+fn pre_funded_reward_and_creator_stats_addresses_are_created() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_accounts_per_creator: 2,
+        ..default_params()
+    });
+    let stats_address = fixture.env.creator_stats_address(&fixture.signer);
+    fixture.env.pre_fund(&fixture.reward_address());
+    fixture.env.pre_fund(&stats_address);
+
+    capped_init(&mut fixture).unwrap();
+
+    let reward_address = fixture.reward_address();
+    assert_eq!(
+        fixture.env.account(&reward_address).unwrap().owner,
+        fixture.env.program_id
+    );
+    assert_eq!(fixture.reward_account().owner, fixture.signer);
+    assert_eq!(
+        fixture.env.lamports(&reward_address),
+        Rent::default().minimum_balance(REWARD_ACCOUNT_SIZE)
+    );
+    let stats =
+        CreatorStats::try_from_account_data(&fixture.env.account(&stats_address).unwrap().data)
+            .unwrap();
+    assert_eq!(stats.accounts_created, 1);
+}
+
+#[test]
+fn pre_funded_referral_code_address_is_created() {
+    let mut fixture = Fixture::configured(default_params());
+    let address = referral_address(&fixture, &REFERRAL_CODE);
+    fixture.env.pre_fund(&address);
+    let referrer = Pubkey::new_unique();
+
+    let mut ix = fixture.instruction(RewardInstruction::SetReferralCode {
+        code: REFERRAL_CODE,
+        referrer,
+    });
+    ix.accounts.push(AccountMeta::new(address, false));
+    fixture.env.process(ix).unwrap();
+
+    init_with_code(&mut fixture, REFERRAL_CODE).unwrap();
+    assert_eq!(fixture.reward_account().referrer, referrer);
+}
+
+#[test]
+fn init_is_capped_per_creator() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_accounts_per_creator: 2,
+        ..default_params()
+    });
+
+    // Closing an account does not give the creation back
+    for _ in 0..2 {
+        capped_init(&mut fixture).unwrap();
+        fixture
+            .process(RewardInstruction::CloseReward { force: false })
+            .unwrap();
+    }
+    assert_eq!(
+        capped_init(&mut fixture),
+        Err(RewardError::CreationCapReached.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+
+    let stats_address = fixture.env.creator_stats_address(&fixture.signer);
+    let stats =
+        CreatorStats::try_from_account_data(&fixture.env.account(&stats_address).unwrap().data)
+            .unwrap();
+    assert_eq!(stats.creator, fixture.signer);
+    assert_eq!(stats.accounts_created, 2);
+}
+
+#[test]
+fn creation_cap_counts_the_relayer_paying_for_fresh_wallets() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_accounts_per_creator: 1,
+        ..default_params()
+    });
+    let relayer = fixture.env.create_wallet();
+    let stats_address = fixture.env.creator_stats_address(&relayer);
+    let sponsored_init = |fixture: &Fixture, user: &Pubkey| {
+        let mut ix = fixture.instruction_signed_by(
+            user,
+            RewardInstruction::Init {
+                idempotency_key: None,
+                referral_code: None,
+                cohort: 0,
+            },
+        );
+        ix.accounts.extend([
+            AccountMeta::new(stats_address, false),
+            AccountMeta::new(relayer, true),
+        ]);
+        ix
+    };
+
+    let first = fixture.env.create_wallet();
+    let ix = sponsored_init(&fixture, &first);
+    fixture.env.process(ix).unwrap();
+    // A new wallet per signup does not get around the cap
+    let second = fixture.env.create_wallet();
+    let ix = sponsored_init(&fixture, &second);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::CreationCapReached.into())
+    );
+    assert!(fixture
+        .env
+        .account(&fixture.env.reward_address(&second))
+        .is_none());
+
+    let stats =
+        CreatorStats::try_from_account_data(&fixture.env.account(&stats_address).unwrap().data)
+            .unwrap();
+    assert_eq!(stats.creator, relayer);
+    assert_eq!(stats.accounts_created, 1);
+}
+
+#[test]
+fn signer_stats_do_not_cover_a_relayer_paid_init() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_accounts_per_creator: 1,
+        ..default_params()
+    });
+    let relayer = fixture.env.create_wallet();
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.extend([
+        AccountMeta::new(fixture.env.creator_stats_address(&fixture.signer), false),
+        AccountMeta::new(relayer, true),
+    ]);
+    assert_eq!(fixture.env.process(ix), Err(RewardError::InvalidPda.into()));
+}
+
+fn init_with_key(fixture: &mut Fixture, key: [u8; 32]) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Init {
+        idempotency_key: Some(key),
+        referral_code: None,
+        cohort: 0,
+    })
+}
+
+#[test]
+fn retrying_init_with_the_same_key_succeeds() {
+    let mut fixture = Fixture::configured(default_params());
+    init_with_key(&mut fixture, [1; 32]).unwrap();
+    fixture.earn(30).unwrap();
+
+    // The retry leaves the account as it is
+    init_with_key(&mut fixture, [1; 32]).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.init_key, [1; 32]);
+    assert_eq!(reward_account.total_points, 30);
+}
+
+#[test]
+fn init_with_another_key_is_rejected() {
+    let mut fixture = Fixture::configured(default_params());
+    init_with_key(&mut fixture, [1; 32]).unwrap();
+
+    assert_eq!(
+        init_with_key(&mut fixture, [2; 32]),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+    assert_eq!(
+        fixture.process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        }),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+    // An all-zero key would match accounts created without one
+    assert_eq!(
+        init_with_key(&mut fixture, [0; 32]),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn init_stops_at_the_signup_cap() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        max_total_users: 3,
+        ..default_params()
+    });
+    let init = || RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    };
+
+    fixture.process(init()).unwrap();
+    for _ in 0..2 {
+        let user = fixture.env.create_wallet();
+        let ix = fixture.instruction_signed_by(&user, init());
+        fixture.env.process(ix).unwrap();
+    }
+    assert_eq!(fixture.env.config().total_users, 3);
+
+    let late = fixture.env.create_wallet();
+    let ix = fixture.instruction_signed_by(&late, init());
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::SignupCapReached.into())
+    );
+    assert!(fixture
+        .env
+        .account(&fixture.env.reward_address(&late))
+        .is_none());
+    assert_eq!(fixture.env.config().total_users, 3);
+}
+
+const REFERRAL_CODE: [u8; 8] = *b"SUMMER24";
+
+fn referral_address(fixture: &Fixture, code: &[u8; 8]) -> Pubkey {
+    find_referral_address(&fixture.env.program_id, code).0
+}
+
+fn init_with_code(fixture: &mut Fixture, code: [u8; 8]) -> Result<(), ProgramError> {
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: Some(code),
+        cohort: 0,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        referral_address(fixture, &code),
+        false,
+    ));
+    fixture.env.process(ix)
+}
+
+// A config that rejects unknown codes, with REFERRAL_CODE crediting the returned referrer
+fn referral_fixture() -> (Fixture, Pubkey) {
+    let mut fixture = Fixture::configured(ConfigParams {
+        reject_unknown_referrals: true,
+        ..default_params()
+    });
+    let referrer = Pubkey::new_unique();
+    let mut ix = fixture.instruction(RewardInstruction::SetReferralCode {
+        code: REFERRAL_CODE,
+        referrer,
+    });
+    ix.accounts.push(AccountMeta::new(
+        referral_address(&fixture, &REFERRAL_CODE),
+        false,
+    ));
+    fixture.env.process(ix).unwrap();
+    (fixture, referrer)
+}
+
+#[test]
+fn init_with_a_referral_code_binds_its_referrer() {
+    let (mut fixture, referrer) = referral_fixture();
+
+    init_with_code(&mut fixture, REFERRAL_CODE).unwrap();
+
+    assert_eq!(fixture.reward_account().referrer, referrer);
+}
+
+#[test]
+fn init_with_an_unknown_referral_code_is_rejected() {
+    let (mut fixture, _) = referral_fixture();
+
+    assert_eq!(
+        init_with_code(&mut fixture, *b"WINTER24"),
+        Err(RewardError::UnknownReferralCode.into())
+    );
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+    assert_eq!(fixture.env.config().total_users, 0);
+}
+
+#[test]
+fn unknown_referral_codes_are_ignored_unless_rejected() {
+    let mut fixture = Fixture::configured(default_params());
+
+    init_with_code(&mut fixture, *b"WINTER24").unwrap();
+
+    assert_eq!(fixture.reward_account().referrer, Pubkey::default());
+}
+
+#[test]
+fn relayer_paid_init_belongs_to_the_user() {
+    let mut fixture = Fixture::configured(default_params());
+    let user = fixture.signer;
+    let relayer = fixture.env.create_wallet();
+    let user_lamports = fixture.env.lamports(&user);
+    let relayer_lamports = fixture.env.lamports(&relayer);
+
+    // The user signs for their own account; the relayer follows and pays
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.push(AccountMeta::new(relayer, true));
+    fixture.env.process(ix).unwrap();
+
+    let reward_address = fixture.env.reward_address(&user);
+    let rent = fixture.env.lamports(&reward_address);
+    assert_eq!(fixture.reward_account().authority, EARN_AUTHORITY);
+    assert_eq!(fixture.reward_account().owner, user);
+    assert_eq!(fixture.env.lamports(&user), user_lamports);
+    assert_eq!(fixture.env.lamports(&relayer), relayer_lamports - rent);
+    assert!(fixture
+        .env
+        .account(&fixture.env.reward_address(&relayer))
+        .is_none());
+}
+
+#[test]
+fn rent_payer_must_sign() {
+    let mut fixture = Fixture::configured(default_params());
+    let relayer = fixture.env.create_wallet();
+
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.push(AccountMeta::new(relayer, false));
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{default_params, Fixture, EARN_AUTHORITY, EXCHANGE_RATE, VAULT_BALANCE};
+use solana_program::{instruction::Instruction, program_pack::Pack};
+use spl_token::state::Account as TokenAccount;
+use wagus_reward_system::instruction::{claim, earn, init, init_vault};
+use wagus_reward_system::{AccountState, ConfigParams, CreatorStats, MEMO_PROGRAM_ID};
+
+// Init, Earn and Claim built by the client helpers, end to end
+#[test]
+fn builders_match_the_processor_account_lists() {
+    let mut fixture = Fixture::configured(default_params());
+    let program_id = fixture.env.program_id;
+    let (signer, config) = (fixture.signer, fixture.env.config());
+
+    let ix = init(&program_id, &signer, &config, None, None, None, 0).unwrap();
+    fixture.env.process(ix).unwrap();
+    let ix = earn(&program_id, &signer, &EARN_AUTHORITY, 100).unwrap();
+    fixture.env.process(ix).unwrap();
+    let ix = claim(
+        &program_id,
+        &signer,
+        &fixture.user_token,
+        &fixture.vault_token,
+        &config,
+        40,
+        Some(100),
+    )
+    .unwrap();
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - 40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn init_vault_builder_creates_the_vault() {
+    let mut fixture = Fixture::new(0);
+    let program_id = fixture.env.program_id;
+    let vault = fixture.env.vault_address(&fixture.mint);
+    fixture.env.remove_account(&vault);
+
+    let ix = init_vault(&program_id, &fixture.signer, &fixture.mint).unwrap();
+    fixture.env.process(ix).unwrap();
+
+    let token_account = TokenAccount::unpack(&fixture.env.account(&vault).unwrap().data).unwrap();
+    assert_eq!(token_account.mint, fixture.mint);
+    assert_eq!(token_account.owner, fixture.env.vault_authority_address());
+}
+
+// Init and Claim under a config asking for every optional account
+#[test]
+fn builders_add_the_accounts_the_config_asks_for() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        require_funded_vault: true,
+        max_accounts_per_creator: 1,
+        require_memo: true,
+        auto_replenish: true,
+        replenish_threshold: VAULT_BALANCE + 1,
+        replenish_amount: 1_000,
+        ..default_params()
+    });
+    let program_id = fixture.env.program_id;
+    let (signer, config) = (fixture.signer, fixture.env.config());
+    let mint_authority = fixture.env.mint_authority_address();
+    fixture
+        .env
+        .set_mint_authority(&fixture.mint, &mint_authority);
+    let relayer = fixture.env.create_wallet();
+
+    let relayer_lamports = fixture.env.lamports(&relayer);
+    let ix = init(&program_id, &signer, &config, Some(&relayer), None, None, 0).unwrap();
+    fixture.env.process(ix).unwrap();
+    assert!(fixture.env.lamports(&relayer) < relayer_lamports);
+    // The relayer paid, so the creation counts against it
+    let stats = fixture.env.creator_stats_address(&relayer);
+    let stats = CreatorStats::try_from_account_data(&fixture.env.account(&stats).unwrap().data);
+    assert_eq!(stats.unwrap().accounts_created, 1);
+
+    fixture.earn(100).unwrap();
+    let memo = Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: b"builder".to_vec(),
+    };
+    let ix = claim(
+        &program_id,
+        &signer,
+        &fixture.user_token,
+        &fixture.vault_token,
+        &config,
+        40,
+        None,
+    )
+    .unwrap();
+    fixture.env.process_transaction(&[memo, ix]).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE + 1_000 - 40 * EXCHANGE_RATE
+    );
+}
@@ -0,0 +1,366 @@
+// End-to-end tests against a `solana-program-test` bank.
+//
+// Unlike the harness in `common`, these run the processor inside the real
+// runtime: transactions go through `BanksClient`, CPIs reach the system
+// program and the bundled SPL Token program, and results are read back from
+// the bank's accounts.
+
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use wagus_reward_system::{
+    find_vault_address, init_vault, process_instruction, AccountState, ConfigParams, RewardAccount,
+    RewardInstruction, BPS_DENOMINATOR,
+};
+
+const EXCHANGE_RATE: u64 = 1_000;
+const VAULT_BALANCE: u64 = 1_000_000;
+
+fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+struct Bank {
+    client: BanksClient,
+    payer: Keypair,
+    program_id: Pubkey,
+}
+
+impl Bank {
+    async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let program_test = ProgramTest::new(
+            "wagus_reward_system",
+            program_id,
+            processor!(process_instruction),
+        );
+        let mut context = program_test.start_with_context().await;
+
+        // The payer is the program's upgrade authority, which InitConfig requires
+        let mut data = vec![0; 45];
+        data[..4].copy_from_slice(&3u32.to_le_bytes());
+        data[12] = 1;
+        data[13..].copy_from_slice(context.payer.pubkey().as_ref());
+        let program_data = Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        context.set_account(&program_data_address(&program_id), &program_data.into());
+
+        Bank {
+            client: context.banks_client,
+            payer: context.payer,
+            program_id,
+        }
+    }
+
+    async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) {
+        let blockhash = self.client.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.client.process_transaction(transaction).await.unwrap();
+    }
+
+    fn reward_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"reward", self.payer.pubkey().as_ref()], &self.program_id).0
+    }
+
+    fn program_data_address(&self) -> Pubkey {
+        program_data_address(&self.program_id)
+    }
+
+    fn config_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"config"], &self.program_id).0
+    }
+
+    fn vault_authority_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault_authority"], &self.program_id).0
+    }
+
+    fn mint_authority_address(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"mint_authority"], &self.program_id).0
+    }
+
+    async fn reward_account(&mut self) -> RewardAccount {
+        let account = self
+            .client
+            .get_account(self.reward_address())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, self.program_id);
+        RewardAccount::try_from_account_data(&account.data).unwrap()
+    }
+
+    async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self
+            .client
+            .get_account(*token_account)
+            .await
+            .unwrap()
+            .unwrap();
+        TokenAccount::unpack(&account.data).unwrap().amount
+    }
+
+    // A mint controlled by the payer, with a vault of VAULT_BALANCE tokens held by
+    // the vault authority PDA and an empty user token account owned by the payer
+    async fn create_token_accounts(&mut self) -> (Pubkey, Pubkey, Pubkey) {
+        let rent = self.client.get_rent().await.unwrap();
+        let payer = self.payer.pubkey();
+        let mint = Keypair::new();
+        let vault = Keypair::new();
+        let user_token = Keypair::new();
+
+        let mut instructions = vec![
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer,
+                None,
+                6,
+            )
+            .unwrap(),
+        ];
+        let vault_authority = self.vault_authority_address();
+        for (token_account, owner) in [(&vault, vault_authority), (&user_token, payer)] {
+            instructions.push(system_instruction::create_account(
+                &payer,
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ));
+            instructions.push(
+                spl_token::instruction::initialize_account3(
+                    &spl_token::id(),
+                    &token_account.pubkey(),
+                    &mint.pubkey(),
+                    &owner,
+                )
+                .unwrap(),
+            );
+        }
+        instructions.push(
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &vault.pubkey(),
+                &payer,
+                &[],
+                VAULT_BALANCE,
+            )
+            .unwrap(),
+        );
+        self.send(&instructions, &[&mint, &vault, &user_token])
+            .await;
+        (mint.pubkey(), vault.pubkey(), user_token.pubkey())
+    }
+
+    // Earn skips the token accounts: signer, reward PDA, the system program, then
+    // the config PDA
+    fn earn_instruction(&self, data: &RewardInstruction) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new(self.reward_address(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.config_address(), false),
+            ],
+            data: borsh::to_vec(data).unwrap(),
+        }
+    }
+
+    // Claim: signer, reward PDA, the token accounts, then the config PDA
+    fn instruction(
+        &self,
+        data: &RewardInstruction,
+        user_token: &Pubkey,
+        vault: &Pubkey,
+        mint: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new(self.reward_address(), false),
+                AccountMeta::new(*user_token, false),
+                AccountMeta::new(*vault, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(self.config_address(), false),
+            ],
+            data: borsh::to_vec(data).unwrap(),
+        }
+    }
+
+    // Init skips the token accounts
+    fn short_instruction(&self, data: &RewardInstruction, mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new(self.reward_address(), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.config_address(), false),
+            ],
+            data: borsh::to_vec(data).unwrap(),
+        }
+    }
+
+    // Config, then the payer's reward account, ready to earn and claim
+    async fn set_up(&mut self) -> (Pubkey, Pubkey, Pubkey) {
+        let (mint, vault, user_token) = self.create_token_accounts().await;
+        let init_config = RewardInstruction::InitConfig {
+            params: ConfigParams {
+                exchange_rate: EXCHANGE_RATE,
+                max_vault_fraction_bps: BPS_DENOMINATOR,
+                earn_authority: self.payer.pubkey(),
+                ..ConfigParams::default()
+            },
+        };
+        let init_config = Instruction {
+            program_id: self.program_id,
+            accounts: vec![
+                AccountMeta::new(self.payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.config_address(), false),
+                AccountMeta::new_readonly(self.program_data_address(), false),
+                AccountMeta::new_readonly(mint, false),
+            ],
+            data: borsh::to_vec(&init_config).unwrap(),
+        };
+        let init = self.short_instruction(
+            &RewardInstruction::Init {
+                idempotency_key: None,
+                referral_code: None,
+                cohort: 0,
+            },
+            &mint,
+        );
+        self.send(&[init_config, init], &[]).await;
+        (mint, vault, user_token)
+    }
+
+    async fn earn(&mut self, points: u32) {
+        let mut earn = self.earn_instruction(&RewardInstruction::Earn { points });
+        // The payer is also the config's earn authority
+        earn.accounts
+            .push(AccountMeta::new_readonly(self.payer.pubkey(), true));
+        self.send(&[earn], &[]).await;
+    }
+}
+
+#[tokio::test]
+async fn init_then_earn_records_points() {
+    let mut bank = Bank::start().await;
+    let (mint, _, _) = bank.set_up().await;
+
+    let reward_account = bank.reward_account().await;
+    assert_eq!(reward_account.total_points, 0);
+    assert_eq!(reward_account.mint, mint);
+    assert_eq!(reward_account.authority, bank.payer.pubkey());
+
+    bank.earn(30).await;
+    bank.earn(12).await;
+
+    let reward_account = bank.reward_account().await;
+    assert_eq!(reward_account.total_points, 42);
+    assert_eq!(reward_account.lifetime_points, 42);
+}
+
+#[tokio::test]
+async fn claim_moves_tokens_from_the_vault() {
+    let mut bank = Bank::start().await;
+    let (mint, vault, user_token) = bank.set_up().await;
+    bank.earn(100).await;
+
+    let claim = RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: Some(100),
+    };
+    let mut claim = bank.instruction(&claim, &user_token, &vault, &mint);
+    claim.accounts.push(AccountMeta::new_readonly(
+        bank.vault_authority_address(),
+        false,
+    ));
+    bank.send(&[claim], &[]).await;
+
+    assert_eq!(bank.reward_account().await.total_points, 60);
+    assert_eq!(bank.reward_account().await.rewards_claimed, 1);
+    assert_eq!(bank.token_balance(&user_token).await, 40 * EXCHANGE_RATE);
+    assert_eq!(
+        bank.token_balance(&vault).await,
+        VAULT_BALANCE - 40 * EXCHANGE_RATE
+    );
+}
+
+#[tokio::test]
+async fn mint_token_mints_into_the_vault() {
+    let mut bank = Bank::start().await;
+    let (mint, _, user_token) = bank.set_up().await;
+    bank.earn(10).await;
+
+    // MintToken only mints into the mint's vault PDA, which InitVault creates
+    let vault = find_vault_address(&bank.program_id, &mint).0;
+    let init_vault = init_vault(&bank.program_id, &bank.payer.pubkey(), &mint).unwrap();
+
+    // Hand the mint to the mint authority PDA
+    let mint_authority = bank.mint_authority_address();
+    let set_authority = spl_token::instruction::set_authority(
+        &spl_token::id(),
+        &mint,
+        Some(&mint_authority),
+        spl_token::instruction::AuthorityType::MintTokens,
+        &bank.payer.pubkey(),
+        &[],
+    )
+    .unwrap();
+    // The vault, mint and token program, then the config and the mint authority PDA
+    let mint_token = Instruction {
+        program_id: bank.program_id,
+        accounts: vec![
+            AccountMeta::new(bank.payer.pubkey(), true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(bank.config_address(), false),
+            AccountMeta::new_readonly(mint_authority, false),
+        ],
+        data: borsh::to_vec(&RewardInstruction::MintToken { amount: 500 }).unwrap(),
+    };
+    bank.send(&[init_vault, set_authority, mint_token], &[])
+        .await;
+
+    assert_eq!(bank.token_balance(&vault).await, 500);
+    assert_eq!(bank.reward_account().await.total_points, 10);
+    assert_eq!(bank.token_balance(&user_token).await, 0);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{default_params, ed25519_instruction, Fixture, EXCHANGE_RATE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{kyc_attestation_message, ConfigParams, RewardError, RewardInstruction};
+
+fn kyc_fixture() -> Fixture {
+    Fixture::with_params(
+        100,
+        ConfigParams {
+            require_kyc: true,
+            ..default_params()
+        },
+    )
+}
+
+fn claim(fixture: &Fixture) -> Instruction {
+    let mut ix = fixture.instruction(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        solana_instructions_sysvar::id(),
+        false,
+    ));
+    ix
+}
+
+#[test]
+fn attested_claim_pays_out() {
+    let mut fixture = kyc_fixture();
+    // The fixture signer is also the config admin
+    let attestation = ed25519_instruction(
+        &fixture.signer,
+        &[7; 64],
+        &kyc_attestation_message(&fixture.signer),
+    );
+
+    let claim = claim(&fixture);
+    fixture
+        .env
+        .process_transaction(&[attestation, claim])
+        .unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn unattested_claim_is_rejected() {
+    let mut fixture = kyc_fixture();
+
+    let claim = claim(&fixture);
+    assert_eq!(
+        fixture.env.process(claim),
+        Err(RewardError::KycRequired.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn attestation_must_come_from_the_admin_for_this_user() {
+    let mut fixture = kyc_fixture();
+    let stranger = Pubkey::new_unique();
+
+    let attestations = [
+        ed25519_instruction(
+            &stranger,
+            &[7; 64],
+            &kyc_attestation_message(&fixture.signer),
+        ),
+        ed25519_instruction(
+            &fixture.signer,
+            &[7; 64],
+            &kyc_attestation_message(&stranger),
+        ),
+    ];
+    for attestation in attestations {
+        let claim = claim(&fixture);
+        assert_eq!(
+            fixture.env.process_transaction(&[attestation, claim]),
+            Err(RewardError::KycRequired.into())
+        );
+    }
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE};
+use solana_program::instruction::{AccountMeta, Instruction};
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction, MEMO_PROGRAM_ID};
+
+fn memo_fixture() -> Fixture {
+    Fixture::with_params(
+        100,
+        ConfigParams {
+            require_memo: true,
+            ..default_params()
+        },
+    )
+}
+
+fn memo(text: &str) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: text.as_bytes().to_vec(),
+    }
+}
+
+fn claim(fixture: &Fixture) -> Instruction {
+    let mut ix = fixture.instruction(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        solana_instructions_sysvar::id(),
+        false,
+    ));
+    ix
+}
+
+#[test]
+fn claim_after_a_memo_pays_out() {
+    let mut fixture = memo_fixture();
+
+    let claim = claim(&fixture);
+    fixture
+        .env
+        .process_transaction(&[memo("invoice 42"), claim])
+        .unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn claim_without_a_memo_is_rejected() {
+    let mut fixture = memo_fixture();
+
+    let ix = claim(&fixture);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::MemoRequired.into())
+    );
+
+    // A memo after the claim does not count
+    let ix = claim(&fixture);
+    assert_eq!(
+        fixture.env.process_transaction(&[ix, memo("too late")]),
+        Err(RewardError::MemoRequired.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::Fixture;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{RewardAccount, RewardError, RewardInstruction};
+
+// Merge the fixture signer's account into `target_owner`'s, both signing
+fn merge(fixture: &Fixture, target_owner: &Pubkey) -> Instruction {
+    let mut ix = fixture.instruction(RewardInstruction::MergeAccounts);
+    ix.accounts.extend([
+        AccountMeta::new_readonly(*target_owner, true),
+        AccountMeta::new(fixture.env.reward_address(target_owner), false),
+    ]);
+    ix
+}
+
+#[test]
+fn merge_sums_balances_and_closes_the_source() {
+    let mut fixture = Fixture::new(0);
+    let mint = fixture.mint;
+    fixture.set_reward_account(&RewardAccount {
+        total_points: 40,
+        lifetime_points: 90,
+        staked_points: 7,
+        rewards_claimed: 2,
+        mint,
+        authority: fixture.signer,
+        ..RewardAccount::default()
+    });
+    let target_owner = fixture.env.create_wallet();
+    fixture.env.set_reward_account(
+        &target_owner,
+        &RewardAccount {
+            total_points: 10,
+            lifetime_points: 15,
+            staked_points: 3,
+            rewards_claimed: 1,
+            mint,
+            authority: target_owner,
+            ..RewardAccount::default()
+        },
+    );
+    let source = fixture.reward_address();
+    let rent = fixture.env.lamports(&source);
+    let signer_lamports = fixture.env.lamports(&fixture.signer);
+
+    let ix = merge(&fixture, &target_owner);
+    fixture.env.process(ix).unwrap();
+
+    let target = fixture.env.reward_account(&target_owner);
+    assert_eq!(target.total_points, 50);
+    assert_eq!(target.lifetime_points, 105);
+    assert_eq!(target.staked_points, 10);
+    assert_eq!(target.rewards_claimed, 3);
+    assert!(fixture.env.account(&source).is_none());
+    assert_eq!(
+        fixture.env.lamports(&fixture.signer),
+        signer_lamports + rent
+    );
+}
+
+#[test]
+fn merge_into_itself_is_rejected() {
+    let mut fixture = Fixture::new(40);
+
+    let ix = merge(&fixture, &fixture.signer);
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.reward_account().total_points, 40);
+}
+
+#[test]
+fn merge_requires_the_target_wallet_to_sign() {
+    let mut fixture = Fixture::new(40);
+    let target_owner = fixture.env.create_wallet();
+    fixture
+        .env
+        .set_reward_account(&target_owner, &RewardAccount::default());
+
+    let mut ix = merge(&fixture, &target_owner);
+    ix.accounts[3].is_signer = false;
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn merge_rejects_an_account_of_another_mint() {
+    let mut fixture = Fixture::new(40);
+    let target_owner = fixture.env.create_wallet();
+    fixture.env.set_reward_account(
+        &target_owner,
+        &RewardAccount {
+            total_points: 10,
+            mint: Pubkey::new_unique(),
+            ..RewardAccount::default()
+        },
+    );
+
+    let ix = merge(&fixture, &target_owner);
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(fixture.reward_account().total_points, 40);
+    assert_eq!(fixture.env.reward_account(&target_owner).total_points, 10);
+}
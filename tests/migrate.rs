@@ -0,0 +1,468 @@
+mod common;
+
+use common::{account_data, default_params, Fixture, TestAccount, EARN_AUTHORITY};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, rent::Rent};
+use wagus_reward_system::{
+    find_reward_address, BaseRewardAccount, RewardAccount, RewardError, RewardInstruction,
+    BASE_REWARD_ACCOUNT_SIZE, DISCRIMINATOR_LEN, LEGACY_REWARD_ACCOUNT_SIZE, REWARD_ACCOUNT_SIZE,
+    REWARD_ACCOUNT_VERSION, REWARD_NOTE_LEN,
+};
+
+// A reward account holding `data` as written by an older layout
+fn legacy_fixture(data: Vec<u8>) -> Fixture {
+    let mut fixture = Fixture::configured(default_params());
+    set_reward_data(&mut fixture, data);
+    fixture
+}
+
+fn set_reward_data(fixture: &mut Fixture, data: Vec<u8>) {
+    let reward_address = fixture.reward_address();
+    let owner = fixture.env.program_id;
+    fixture.env.set_account(
+        reward_address,
+        TestAccount {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+        },
+    );
+}
+
+// A reward account as the old lib.rs stored it, without a mint
+fn legacy_data(total_points: u32, rewards_claimed: u32) -> Vec<u8> {
+    [total_points.to_le_bytes(), rewards_claimed.to_le_bytes()].concat()
+}
+
+// A reward account as the first release stored it
+fn base_data(total_points: u32, rewards_claimed: u32, mint: Pubkey) -> Vec<u8> {
+    let data = borsh::to_vec(&BaseRewardAccount {
+        total_points,
+        rewards_claimed,
+        mint,
+    })
+    .unwrap();
+    assert_eq!(data.len(), BASE_REWARD_ACCOUNT_SIZE);
+    data
+}
+
+fn base_fixture(total_points: u32, rewards_claimed: u32) -> Fixture {
+    let mut fixture = Fixture::configured(default_params());
+    let data = base_data(total_points, rewards_claimed, fixture.mint);
+    set_reward_data(&mut fixture, data);
+    fixture
+}
+
+#[test]
+fn migrate_expands_a_base_layout_account() {
+    let mut fixture = base_fixture(120, 3);
+    let signer_lamports = fixture.env.lamports(&fixture.signer);
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+
+    let reward_address = fixture.reward_address();
+    let account = fixture.env.account(&reward_address).unwrap();
+    assert_eq!(account.data.len(), REWARD_ACCOUNT_SIZE);
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(reward_account.total_points, 120);
+    assert_eq!(reward_account.rewards_claimed, 3);
+    assert_eq!(reward_account.mint, fixture.mint);
+    assert_eq!(reward_account.lifetime_points, 120);
+    assert_eq!(reward_account.authority, EARN_AUTHORITY);
+    assert_eq!(reward_account.owner, fixture.signer);
+
+    // The signer paid the extra rent
+    let rent = Rent::default();
+    let top_up =
+        rent.minimum_balance(REWARD_ACCOUNT_SIZE) - rent.minimum_balance(BASE_REWARD_ACCOUNT_SIZE);
+    assert_eq!(
+        fixture.env.lamports(&reward_address),
+        rent.minimum_balance(REWARD_ACCOUNT_SIZE)
+    );
+    assert_eq!(
+        fixture.env.lamports(&fixture.signer),
+        signer_lamports - top_up
+    );
+}
+
+#[test]
+fn migrate_expands_a_legacy_account() {
+    let mut fixture = legacy_fixture(legacy_data(120, 3));
+    let signer_lamports = fixture.env.lamports(&fixture.signer);
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+
+    let reward_address = fixture.reward_address();
+    let account = fixture.env.account(&reward_address).unwrap();
+    assert_eq!(account.data.len(), REWARD_ACCOUNT_SIZE);
+
+    // The mint comes from the passed mint account
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(reward_account.total_points, 120);
+    assert_eq!(reward_account.rewards_claimed, 3);
+    assert_eq!(reward_account.mint, fixture.mint);
+    assert_eq!(reward_account.lifetime_points, 120);
+    assert_eq!(reward_account.authority, EARN_AUTHORITY);
+    assert_eq!(reward_account.owner, fixture.signer);
+
+    let rent = Rent::default();
+    let top_up = rent.minimum_balance(REWARD_ACCOUNT_SIZE)
+        - rent.minimum_balance(LEGACY_REWARD_ACCOUNT_SIZE);
+    assert_eq!(
+        fixture.env.lamports(&fixture.signer),
+        signer_lamports - top_up
+    );
+
+    // Migrating again changes nothing
+    let migrated = fixture.env.account(&reward_address).unwrap().clone();
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+    assert_eq!(
+        fixture.env.account(&reward_address).unwrap().data,
+        migrated.data
+    );
+}
+
+#[test]
+fn migrate_rejects_a_foreign_mint_for_a_legacy_account() {
+    let mut fixture = legacy_fixture(legacy_data(120, 3));
+    let mut ix = fixture.instruction(RewardInstruction::MigrateLegacyLayout);
+    ix.accounts[2].pubkey = Pubkey::new_unique();
+
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(
+        fixture.env.account(&fixture.reward_address()).unwrap().data,
+        legacy_data(120, 3)
+    );
+}
+
+#[test]
+fn earn_needs_the_mint_to_migrate_a_legacy_account() {
+    let mut fixture = legacy_fixture(legacy_data(120, 3));
+
+    assert_eq!(fixture.earn(5), Err(RewardError::MissingAccount.into()));
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+    fixture.earn(5).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 125);
+}
+
+#[test]
+fn init_migrates_a_legacy_account() {
+    let mut fixture = legacy_fixture(legacy_data(40, 2));
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 40);
+    assert_eq!(reward_account.rewards_claimed, 2);
+    assert_eq!(reward_account.mint, fixture.mint);
+}
+
+#[test]
+fn migrate_keeps_the_stored_mint() {
+    let stored_mint = Pubkey::new_unique();
+    let mut fixture = legacy_fixture(base_data(75, 1, stored_mint));
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 75);
+    assert_eq!(reward_account.rewards_claimed, 1);
+    assert_eq!(reward_account.mint, stored_mint);
+    assert_eq!(reward_account.authority, EARN_AUTHORITY);
+
+    // Migrated accounts can earn again
+    fixture.earn(5).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 80);
+}
+
+#[test]
+fn migrate_is_idempotent() {
+    let mut fixture = base_fixture(120, 3);
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+    let reward_address = fixture.reward_address();
+    let migrated = fixture.env.account(&reward_address).unwrap().clone();
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+
+    let account = fixture.env.account(&reward_address).unwrap();
+    assert_eq!(account.data, migrated.data);
+    assert_eq!(account.lamports, migrated.lamports);
+}
+
+#[test]
+fn migrate_rejects_an_unknown_layout() {
+    let mut fixture = legacy_fixture(vec![0; 12]);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MigrateLegacyLayout),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn earn_migrates_a_base_layout_account() {
+    let mut fixture = base_fixture(120, 3);
+
+    fixture.earn(5).unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(reward_account.total_points, 125);
+    assert_eq!(reward_account.lifetime_points, 125);
+    assert_eq!(reward_account.rewards_claimed, 3);
+}
+
+#[test]
+fn init_migrates_instead_of_reinitializing() {
+    let mut fixture = base_fixture(120, 3);
+
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+    assert_eq!(fixture.reward_account().total_points, 120);
+
+    // Once current, Init refuses as before
+    assert_eq!(
+        fixture.process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        }),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+}
+
+// Init without the system program, which reinitializing does not need
+fn reinit(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    let mut ix = fixture.instruction(RewardInstruction::Init {
+        idempotency_key: None,
+        referral_code: None,
+        cohort: 0,
+    });
+    ix.accounts.remove(3);
+    fixture.env.process(ix)
+}
+
+#[test]
+fn reinit_without_the_system_program() {
+    let mut fixture = Fixture::configured(default_params());
+    // Already holding rent for the current layout, so nothing has to be transferred
+    let reward_address = fixture.reward_address();
+    let owner = fixture.env.program_id;
+    fixture.env.set_account(
+        reward_address,
+        TestAccount {
+            lamports: Rent::default().minimum_balance(REWARD_ACCOUNT_SIZE),
+            data: base_data(120, 3, fixture.mint),
+            owner,
+        },
+    );
+
+    reinit(&mut fixture).unwrap();
+    assert_eq!(fixture.reward_account().version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(fixture.reward_account().total_points, 120);
+
+    assert_eq!(
+        reinit(&mut fixture),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+}
+
+#[test]
+fn reinit_topping_up_rent_needs_the_system_program() {
+    let mut fixture = base_fixture(120, 3);
+
+    assert_eq!(
+        reinit(&mut fixture),
+        Err(RewardError::MissingAccount.into())
+    );
+    assert_eq!(
+        fixture.env.account(&fixture.reward_address()).unwrap().data,
+        base_data(120, 3, fixture.mint)
+    );
+}
+
+#[test]
+fn migrate_rejects_an_unknown_version() {
+    let mut data = account_data(&RewardAccount::default());
+    data[DISCRIMINATOR_LEN] = REWARD_ACCOUNT_VERSION + 1;
+    let mut fixture = legacy_fixture(data);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MigrateLegacyLayout),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn set_note_migrates_a_base_layout_account() {
+    let mut fixture = base_fixture(25, 0);
+
+    let note = [7; REWARD_NOTE_LEN];
+    fixture
+        .process(RewardInstruction::SetNote { note })
+        .unwrap();
+
+    let reward_address = fixture.reward_address();
+    assert_eq!(
+        fixture.env.account(&reward_address).unwrap().data.len(),
+        REWARD_ACCOUNT_SIZE
+    );
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(reward_account.total_points, 25);
+    assert_eq!(reward_account.note, note);
+}
+
+#[test]
+fn migration_stores_the_reward_bump() {
+    let mut fixture = base_fixture(30, 0);
+
+    fixture.earn(5).unwrap();
+
+    let (_, bump) = find_reward_address(&fixture.env.program_id, &fixture.signer);
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.bump, bump);
+    assert_eq!(reward_account.total_points, 35);
+
+    // Later instructions check the address against the stored bump
+    fixture.earn(5).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 40);
+}
+
+#[test]
+fn migration_widens_the_point_counters() {
+    let mut fixture = base_fixture(u32::MAX - 5, 0);
+
+    fixture
+        .process(RewardInstruction::MigrateLegacyLayout)
+        .unwrap();
+    assert_eq!(fixture.reward_account().total_points, u32::MAX as u64 - 5);
+
+    // Balances now grow past what the old counters could hold
+    fixture.earn(10).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, u32::MAX as u64 + 5);
+    assert_eq!(reward_account.lifetime_points, u32::MAX as u64 + 5);
+}
+
+// The shared [b"reward"] account of the old lib.rs holding `data`
+fn global_fixture(data: Vec<u8>) -> Fixture {
+    let mut fixture = Fixture::configured(default_params());
+    let legacy_address = fixture.env.legacy_reward_address();
+    let owner = fixture.env.program_id;
+    fixture.env.set_account(
+        legacy_address,
+        TestAccount {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+        },
+    );
+    fixture
+}
+
+#[test]
+fn migrate_global_moves_the_legacy_account_to_the_admin() {
+    let mut fixture = global_fixture(legacy_data(80, 4));
+    let legacy_address = fixture.env.legacy_reward_address();
+    let users = fixture.env.config().total_users;
+
+    fixture
+        .process(RewardInstruction::MigrateGlobalAccount)
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.version, REWARD_ACCOUNT_VERSION);
+    assert_eq!(reward_account.total_points, 80);
+    assert_eq!(reward_account.rewards_claimed, 4);
+    assert_eq!(reward_account.mint, fixture.mint);
+    assert_eq!(reward_account.owner, fixture.signer);
+    let (_, bump) = find_reward_address(&fixture.env.program_id, &fixture.signer);
+    assert_eq!(reward_account.bump, bump);
+    assert_eq!(fixture.env.config().total_users, users + 1);
+
+    // The shared account is closed, so it cannot be moved twice
+    assert!(fixture.env.account(&legacy_address).is_none());
+    assert!(fixture
+        .process(RewardInstruction::MigrateGlobalAccount)
+        .is_err());
+
+    fixture.earn(5).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 85);
+}
+
+#[test]
+fn migrate_global_keeps_the_stored_mint_of_a_base_layout() {
+    let stored_mint = Pubkey::new_unique();
+    let mut fixture = global_fixture(base_data(30, 1, stored_mint));
+
+    fixture
+        .process(RewardInstruction::MigrateGlobalAccount)
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 30);
+    assert_eq!(reward_account.mint, stored_mint);
+}
+
+#[test]
+fn migrate_global_is_admin_only() {
+    let mut fixture = global_fixture(legacy_data(80, 4));
+    let stranger = fixture.env.create_wallet();
+
+    let ix = fixture.instruction_signed_by(&stranger, RewardInstruction::MigrateGlobalAccount);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    let legacy_address = fixture.env.legacy_reward_address();
+    assert_eq!(
+        fixture.env.account(&legacy_address).unwrap().data,
+        legacy_data(80, 4)
+    );
+}
+
+#[test]
+fn migrate_global_refuses_an_existing_reward_account() {
+    let mut fixture = global_fixture(legacy_data(80, 4));
+    fixture
+        .process(RewardInstruction::Init {
+            idempotency_key: None,
+            referral_code: None,
+            cohort: 0,
+        })
+        .unwrap();
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MigrateGlobalAccount),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+}
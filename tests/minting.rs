@@ -0,0 +1,203 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE, VAULT_BALANCE};
+use solana_program::{instruction::AccountMeta, program_error::ProgramError, program_pack::Pack};
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction};
+
+// A vault low enough to be replenished on every claim, with minting paused
+fn paused_minting_fixture() -> Fixture {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            auto_replenish: true,
+            replenish_threshold: VAULT_BALANCE + 1,
+            replenish_amount: 500_000,
+            max_supply: 10 * VAULT_BALANCE,
+            ..default_params()
+        },
+    );
+    let mint_authority = fixture.env.mint_authority_address();
+    fixture
+        .env
+        .set_mint_authority(&fixture.mint, &mint_authority);
+    fixture
+        .process(RewardInstruction::SetMintingPaused { paused: true })
+        .unwrap();
+    fixture
+}
+
+#[test]
+fn paused_minting_rejects_mint_token() {
+    let mut fixture = paused_minting_fixture();
+    assert!(fixture.env.config().minting_paused);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 10 }),
+        Err(RewardError::MintingPaused.into())
+    );
+
+    fixture
+        .process(RewardInstruction::SetMintingPaused { paused: false })
+        .unwrap();
+    fixture
+        .process(RewardInstruction::MintToken { amount: 10 })
+        .unwrap();
+}
+
+#[test]
+fn claims_pay_from_the_vault_without_replenishing() {
+    let mut fixture = paused_minting_fixture();
+
+    let mut ix = fixture.instruction(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    });
+    ix.accounts.push(AccountMeta::new_readonly(
+        fixture.env.mint_authority_address(),
+        false,
+    ));
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - 40 * EXCHANGE_RATE
+    );
+    assert_eq!(
+        fixture.env.mint(&fixture.mint).unwrap().supply,
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn set_minting_paused_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+    fixture
+        .env
+        .set_reward_account(&intruder, &fixture.reward_account());
+
+    let ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::SetMintingPaused { paused: true },
+    );
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(!fixture.env.config().minting_paused);
+}
+
+// The mint authority PDA controls the mint, with minting capped at `max_supply`
+fn capped_fixture(max_supply: u64) -> Fixture {
+    let mut fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            max_supply,
+            ..default_params()
+        },
+    );
+    let mint_authority = fixture.env.mint_authority_address();
+    fixture
+        .env
+        .set_mint_authority(&fixture.mint, &mint_authority);
+    fixture
+}
+
+#[test]
+fn mint_token_mints_into_the_vault_up_to_the_cap() {
+    // Tokens minted before the program took over the mint do not count
+    let mut fixture = capped_fixture(500);
+
+    for amount in [200, 300] {
+        fixture
+            .process(RewardInstruction::MintToken { amount })
+            .unwrap();
+    }
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE + 500
+    );
+    assert_eq!(fixture.env.config().total_minted, 500);
+
+    // Not a single token past the cap
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 1 }),
+        Err(RewardError::SupplyCapExceeded.into())
+    );
+    assert_eq!(
+        fixture.env.mint(&fixture.mint).unwrap().supply,
+        VAULT_BALANCE + 500
+    );
+    assert_eq!(fixture.env.config().total_minted, 500);
+
+    // Burning tokens frees no room under the cap
+    let mut mint = fixture.env.mint(&fixture.mint).unwrap();
+    mint.supply -= 500;
+    let mut account = fixture.env.account(&fixture.mint).unwrap().clone();
+    mint.pack_into_slice(&mut account.data);
+    fixture.env.set_account(fixture.mint, account);
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 1 }),
+        Err(RewardError::SupplyCapExceeded.into())
+    );
+}
+
+#[test]
+fn mint_token_only_mints_into_the_vault() {
+    let mut fixture = capped_fixture(0);
+    // A token account of the mint the signer holds itself
+    fixture.vault_token = fixture.user_token;
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 10 }),
+        Err(RewardError::InvalidVault.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn mint_token_only_mints_into_the_vault_pda() {
+    let mut fixture = capped_fixture(0);
+    // A token account of the mint held by the vault authority, but not the vault PDA
+    let vault_authority = fixture.env.vault_authority_address();
+    let mint = fixture.mint;
+    fixture.vault_token = fixture.env.create_token_account(&mint, &vault_authority, 0);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 10 }),
+        Err(RewardError::InvalidVault.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.vault_token), 0);
+}
+
+#[test]
+fn mint_token_only_mints_the_reward_mint() {
+    let mut fixture = capped_fixture(0);
+    let mint_authority = fixture.env.mint_authority_address();
+    let other_mint = fixture.env.create_mint(&mint_authority, 6);
+    let mut ix = fixture.instruction(RewardInstruction::MintToken { amount: 10 });
+    ix.accounts[2].pubkey = other_mint;
+
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(fixture.env.mint(&other_mint).unwrap().supply, 0);
+}
+
+#[test]
+fn mint_token_requires_admin() {
+    let mut fixture = capped_fixture(0);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = fixture.instruction_signed_by(&intruder, RewardInstruction::MintToken { amount: 10 });
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use common::Fixture;
+use wagus_reward_system::{RewardInstruction, REWARD_NOTE_LEN};
+
+fn note() -> [u8; REWARD_NOTE_LEN] {
+    std::array::from_fn(|i| i as u8 ^ 0xa5)
+}
+
+#[test]
+fn set_note_stores_the_bytes_as_given() {
+    let mut fixture = Fixture::new(40);
+    let before = fixture.reward_account();
+
+    fixture
+        .process(RewardInstruction::SetNote { note: note() })
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.note, note());
+    assert_eq!(reward_account.total_points, before.total_points);
+    assert_eq!(reward_account.mint, before.mint);
+    assert_eq!(reward_account.authority, before.authority);
+
+    // Overwriting with zeroes clears it
+    fixture
+        .process(RewardInstruction::SetNote {
+            note: [0; REWARD_NOTE_LEN],
+        })
+        .unwrap();
+    assert_eq!(fixture.reward_account().note, [0; REWARD_NOTE_LEN]);
+}
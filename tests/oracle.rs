@@ -0,0 +1,213 @@
+mod common;
+
+use common::{default_params, ed25519_instruction, Fixture, VAULT_BALANCE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{
+    kyc_attestation_message, oracle_claim_message, ConfigParams, RewardError, RewardInstruction,
+    MEMO_PROGRAM_ID,
+};
+
+const AMOUNT: u64 = 25_000;
+const NONCE: u64 = 3;
+const EXPIRY_TS: i64 = 1_000;
+const SIGNATURE: [u8; 64] = [9; 64];
+
+struct Oracle {
+    fixture: Fixture,
+    oracle: Pubkey,
+}
+
+fn oracle_fixture() -> Oracle {
+    oracle_fixture_with(default_params())
+}
+
+fn oracle_fixture_with(params: ConfigParams) -> Oracle {
+    let oracle = Pubkey::new_unique();
+    let fixture = Fixture::with_params(0, ConfigParams { oracle, ..params });
+    Oracle { fixture, oracle }
+}
+
+// The oracle's signature check followed by the claim it authorizes
+fn signed_claim(fixture: &Fixture, signer: &Pubkey, amount: u64) -> [Instruction; 2] {
+    signed_claim_with(fixture, signer, amount, NONCE, SIGNATURE)
+}
+
+fn signed_claim_with(
+    fixture: &Fixture,
+    signer: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    signature: [u8; 64],
+) -> [Instruction; 2] {
+    let message = oracle_claim_message(&fixture.signer, amount, nonce, EXPIRY_TS);
+    let mut claim = fixture.instruction(RewardInstruction::ClaimSigned {
+        amount,
+        nonce,
+        expiry_ts: EXPIRY_TS,
+        signature,
+    });
+    claim.accounts.extend([
+        AccountMeta::new_readonly(solana_instructions_sysvar::id(), false),
+        AccountMeta::new(fixture.env.nonce_registry_address(&fixture.signer), false),
+    ]);
+    [ed25519_instruction(signer, &signature, &message), claim]
+}
+
+#[test]
+fn oracle_signed_claim_pays_out_once() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture();
+    fixture.env.set_clock(EXPIRY_TS);
+
+    let instructions = signed_claim(&fixture, &oracle, AMOUNT);
+    fixture.env.process_transaction(&instructions).unwrap();
+
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), AMOUNT);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - AMOUNT
+    );
+    assert_eq!(fixture.reward_account().rewards_claimed, 1);
+
+    // Replaying the same authorization is rejected
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::VoucherAlreadyUsed.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), AMOUNT);
+
+    // A later nonce pays out again
+    let instructions = signed_claim_with(&fixture, &oracle, AMOUNT, NONCE + 1, SIGNATURE);
+    fixture.env.process_transaction(&instructions).unwrap();
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 2 * AMOUNT);
+}
+
+#[test]
+fn expired_oracle_signature_is_rejected() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture();
+    fixture.env.set_clock(EXPIRY_TS + 1);
+
+    let instructions = signed_claim(&fixture, &oracle, AMOUNT);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::OracleSignatureExpired.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn claim_signed_by_someone_else_is_rejected() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture();
+    let stranger = Pubkey::new_unique();
+
+    // Signed by a stranger, or by the oracle over a different amount
+    let forged = signed_claim(&fixture, &stranger, AMOUNT);
+    let mut altered = signed_claim(&fixture, &oracle, AMOUNT);
+    altered[1] = signed_claim(&fixture, &oracle, 2 * AMOUNT)[1].clone();
+    for instructions in [forged, altered] {
+        assert_eq!(
+            fixture.env.process_transaction(&instructions),
+            Err(RewardError::InvalidOracleSignature.into())
+        );
+    }
+
+    // Without the signature check in front of it
+    let [_, claim] = signed_claim(&fixture, &oracle, AMOUNT);
+    assert_eq!(
+        fixture.env.process(claim),
+        Err(RewardError::InvalidOracleSignature.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn another_signature_over_the_same_claim_is_not_paid_again() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture();
+    fixture.env.set_clock(EXPIRY_TS);
+
+    let instructions = signed_claim(&fixture, &oracle, AMOUNT);
+    fixture.env.process_transaction(&instructions).unwrap();
+
+    // A second valid encoding of the signature, such as a malleated one, still carries the used nonce
+    let instructions = signed_claim_with(&fixture, &oracle, AMOUNT, NONCE, [8; 64]);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::VoucherAlreadyUsed.into())
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), AMOUNT);
+}
+
+#[test]
+fn signed_claim_needs_the_configured_memo() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture_with(ConfigParams {
+        require_memo: true,
+        ..default_params()
+    });
+    fixture.env.set_clock(EXPIRY_TS);
+
+    let instructions = signed_claim(&fixture, &oracle, AMOUNT);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::MemoRequired.into())
+    );
+
+    let memo = Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: b"order 42".to_vec(),
+    };
+    let [signature_check, claim] = instructions;
+    fixture
+        .env
+        .process_transaction(&[memo, signature_check, claim])
+        .unwrap();
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), AMOUNT);
+}
+
+#[test]
+fn signed_claim_needs_the_configured_kyc_attestation() {
+    let Oracle {
+        mut fixture,
+        oracle,
+    } = oracle_fixture_with(ConfigParams {
+        require_kyc: true,
+        ..default_params()
+    });
+    fixture.env.set_clock(EXPIRY_TS);
+
+    let instructions = signed_claim(&fixture, &oracle, AMOUNT);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::KycRequired.into())
+    );
+
+    // The admin's attestation goes ahead of the oracle's signature
+    let attestation = ed25519_instruction(
+        &fixture.signer,
+        &[7; 64],
+        &kyc_attestation_message(&fixture.signer),
+    );
+    let [signature_check, claim] = instructions;
+    fixture
+        .env
+        .process_transaction(&[attestation, signature_check, claim])
+        .unwrap();
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), AMOUNT);
+}
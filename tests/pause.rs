@@ -0,0 +1,97 @@
+mod common;
+
+use common::{default_params, Fixture, EARN_AUTHORITY, EXCHANGE_RATE};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use wagus_reward_system::{ConfigParams, RewardError, RewardInstruction};
+
+// SetPaused signed by the earn authority
+fn set_paused(fixture: &mut Fixture, paused: bool) -> Result<(), ProgramError> {
+    set_paused_by(fixture, &EARN_AUTHORITY, paused)
+}
+
+fn set_paused_by(fixture: &mut Fixture, signer: &Pubkey, paused: bool) -> Result<(), ProgramError> {
+    let user = fixture.signer;
+    let mut ix =
+        fixture.instruction_signed_by(signer, RewardInstruction::SetPaused { user, paused });
+    ix.accounts[1].pubkey = fixture.reward_address();
+    fixture.env.process(ix)
+}
+
+fn claim(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    })
+}
+
+#[test]
+fn paused_account_rejects_earn_and_claim() {
+    let mut fixture = Fixture::new(100);
+
+    set_paused(&mut fixture, true).unwrap();
+    assert!(fixture.reward_account().paused);
+
+    assert_eq!(claim(&mut fixture), Err(RewardError::Paused.into()));
+    assert_eq!(fixture.earn(10), Err(RewardError::Paused.into()));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn unpausing_restores_claims() {
+    let mut fixture = Fixture::new(100);
+    set_paused(&mut fixture, true).unwrap();
+
+    set_paused(&mut fixture, false).unwrap();
+    claim(&mut fixture).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 60);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn set_paused_requires_the_earn_authority_or_the_admin() {
+    let mut fixture = Fixture::new(100);
+    let stranger = fixture.env.create_wallet();
+    // The authority stored on the account grants nothing
+    let mut reward_account = fixture.reward_account();
+    reward_account.authority = stranger;
+    fixture.set_reward_account(&reward_account);
+
+    assert_eq!(
+        set_paused_by(&mut fixture, &stranger, true),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(!fixture.reward_account().paused);
+
+    // The admin pauses the account without the earn authority
+    let admin = fixture.env.config().admin;
+    set_paused_by(&mut fixture, &admin, true).unwrap();
+    assert!(fixture.reward_account().paused);
+}
+
+#[test]
+fn set_paused_follows_a_rotated_earn_authority() {
+    let mut fixture = Fixture::new(100);
+    let new_authority = fixture.env.create_wallet();
+    fixture
+        .process(RewardInstruction::UpdateConfig {
+            params: ConfigParams {
+                earn_authority: new_authority,
+                ..default_params()
+            },
+        })
+        .unwrap();
+
+    // The account still records the old authority, which lost the right to pause it
+    assert_eq!(fixture.reward_account().authority, EARN_AUTHORITY);
+    assert_eq!(
+        set_paused(&mut fixture, true),
+        Err(ProgramError::IncorrectAuthority)
+    );
+
+    set_paused_by(&mut fixture, &new_authority, true).unwrap();
+    assert!(fixture.reward_account().paused);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::Fixture;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{RewardAccount, RewardError, RewardInstruction};
+
+// A guild pool with no points of its own
+fn create_pool(fixture: &mut Fixture) -> Pubkey {
+    let pool_owner = fixture.env.create_wallet();
+    let mint = fixture.mint;
+    fixture.env.set_reward_account(
+        &pool_owner,
+        &RewardAccount {
+            mint,
+            authority: pool_owner,
+            ..RewardAccount::default()
+        },
+    );
+    pool_owner
+}
+
+fn create_member(fixture: &mut Fixture, total_points: u64) -> Pubkey {
+    let member = fixture.env.create_wallet();
+    let mint = fixture.mint;
+    fixture.env.set_reward_account(
+        &member,
+        &RewardAccount {
+            total_points,
+            lifetime_points: total_points,
+            mint,
+            authority: member,
+            ..RewardAccount::default()
+        },
+    );
+    member
+}
+
+// `member` pooling `amount` into `pool_owner`'s reward account
+fn pool(fixture: &Fixture, member: &Pubkey, pool_owner: &Pubkey, amount: u32) -> Instruction {
+    let mut ix = fixture.instruction_signed_by(member, RewardInstruction::PoolPoints { amount });
+    ix.accounts.extend([
+        AccountMeta::new_readonly(*pool_owner, false),
+        AccountMeta::new(fixture.env.reward_address(pool_owner), false),
+    ]);
+    ix
+}
+
+#[test]
+fn members_pool_into_one_account() {
+    let mut fixture = Fixture::new(0);
+    let pool_owner = create_pool(&mut fixture);
+    let members = [
+        create_member(&mut fixture, 50),
+        create_member(&mut fixture, 30),
+        create_member(&mut fixture, 20),
+    ];
+
+    for (member, amount) in members.iter().zip([40, 30, 5]) {
+        let ix = pool(&fixture, member, &pool_owner, amount);
+        fixture.env.process(ix).unwrap();
+    }
+
+    assert_eq!(fixture.env.reward_account(&pool_owner).total_points, 75);
+    let remaining: Vec<u64> = members
+        .iter()
+        .map(|member| fixture.env.reward_account(member).total_points)
+        .collect();
+    assert_eq!(remaining, [10, 0, 15]);
+    // Lifetime history stays with the members
+    assert_eq!(fixture.env.reward_account(&members[0]).lifetime_points, 50);
+}
+
+#[test]
+fn pooling_another_members_points_is_rejected() {
+    let mut fixture = Fixture::new(0);
+    let pool_owner = create_pool(&mut fixture);
+    let member = create_member(&mut fixture, 50);
+
+    // The pool owner signs, pointing at the member's account
+    let mut ix = pool(&fixture, &pool_owner, &pool_owner, 40);
+    ix.accounts[1].pubkey = fixture.env.reward_address(&member);
+    assert_eq!(fixture.env.process(ix), Err(RewardError::InvalidPda.into()));
+    assert_eq!(fixture.env.reward_account(&member).total_points, 50);
+}
+
+#[test]
+fn pool_rejects_invalid_amounts() {
+    let mut fixture = Fixture::new(0);
+    let pool_owner = create_pool(&mut fixture);
+    let member = create_member(&mut fixture, 50);
+
+    let ix = pool(&fixture, &member, &pool_owner, 51);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::NotEnoughPoints.into())
+    );
+    let ix = pool(&fixture, &member, &pool_owner, 0);
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+    let ix = pool(&fixture, &member, &member, 10);
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.env.reward_account(&member).total_points, 50);
+}
+
+#[test]
+fn pooling_into_an_account_of_another_mint_is_rejected() {
+    let mut fixture = Fixture::new(0);
+    let pool_owner = fixture.env.create_wallet();
+    fixture.env.set_reward_account(
+        &pool_owner,
+        &RewardAccount {
+            mint: Pubkey::new_unique(),
+            ..RewardAccount::default()
+        },
+    );
+    let member = create_member(&mut fixture, 50);
+
+    let ix = pool(&fixture, &member, &pool_owner, 20);
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(fixture.env.reward_account(&member).total_points, 50);
+}
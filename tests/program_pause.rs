@@ -0,0 +1,110 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{Fixture, EXCHANGE_RATE};
+use solana_program::program_error::ProgramError;
+use wagus_reward_system::{BlockReason, Eligibility, RewardError, RewardInstruction, ACTION_EARN};
+
+fn set_program_paused(fixture: &mut Fixture, paused: bool) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::SetProgramPaused { paused })
+}
+
+fn claim(fixture: &mut Fixture) -> Result<(), ProgramError> {
+    fixture.process(RewardInstruction::Claim {
+        required_points: 40,
+        expected_total_points: None,
+    })
+}
+
+#[test]
+fn paused_program_rejects_earn_claim_and_mint() {
+    let mut fixture = Fixture::new(100);
+
+    set_program_paused(&mut fixture, true).unwrap();
+    assert!(fixture.env.config().program_paused);
+
+    assert_eq!(fixture.earn(10), Err(RewardError::AdminPaused.into()));
+    assert_eq!(claim(&mut fixture), Err(RewardError::AdminPaused.into()));
+    assert_eq!(
+        fixture.process(RewardInstruction::MintToken { amount: 10 }),
+        Err(RewardError::AdminPaused.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+
+    fixture
+        .process(RewardInstruction::CanPerform {
+            action: ACTION_EARN,
+        })
+        .unwrap();
+    let eligibility = Eligibility::try_from_slice(&fixture.env.return_data().unwrap()).unwrap();
+    assert_eq!(eligibility.reason, BlockReason::AdminPaused);
+}
+
+#[test]
+fn paused_program_rejects_every_point_debit_and_payout() {
+    let mut fixture = Fixture::new(100);
+    set_program_paused(&mut fixture, true).unwrap();
+
+    let debits = [
+        || RewardInstruction::ClaimAndStake {
+            required_points: 40,
+            amount: 40 * EXCHANGE_RATE,
+        },
+        || RewardInstruction::ScheduleClaim {
+            required_points: 40,
+            execute_after_ts: 0,
+        },
+        || RewardInstruction::ExecuteScheduledClaim,
+        || RewardInstruction::OpenStream {
+            required_points: 40,
+            total_amount: 40 * EXCHANGE_RATE,
+            duration: 60,
+        },
+        || RewardInstruction::WithdrawStream,
+        || RewardInstruction::CancelStream,
+        || RewardInstruction::PoolPoints { amount: 40 },
+        || RewardInstruction::MergeAccounts,
+    ];
+    for debit in debits {
+        assert_eq!(
+            fixture.process(debit()),
+            Err(RewardError::AdminPaused.into())
+        );
+    }
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn unpausing_the_program_restores_claims() {
+    let mut fixture = Fixture::new(100);
+    set_program_paused(&mut fixture, true).unwrap();
+
+    set_program_paused(&mut fixture, false).unwrap();
+    claim(&mut fixture).unwrap();
+    fixture.earn(10).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, 70);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+}
+
+#[test]
+fn set_program_paused_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let intruder = fixture.env.create_wallet();
+    fixture
+        .env
+        .set_reward_account(&intruder, &fixture.reward_account());
+
+    let ix = fixture.instruction_signed_by(
+        &intruder,
+        RewardInstruction::SetProgramPaused { paused: true },
+    );
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(!fixture.env.config().program_paused);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{default_params, Fixture};
+use wagus_reward_system::{ConfigParams, RewardError};
+
+const CAPACITY: u32 = 100;
+const REFILL_PER_SEC: u32 = 10;
+
+fn rate_limited_fixture() -> Fixture {
+    let fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            earn_bucket_capacity: CAPACITY,
+            earn_refill_per_sec: REFILL_PER_SEC,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(1_000);
+    fixture
+}
+
+#[test]
+fn earns_beyond_the_bucket_are_rejected_until_it_refills() {
+    let mut fixture = rate_limited_fixture();
+
+    // A fresh account starts with a full bucket
+    fixture.earn(60).unwrap();
+    fixture.earn(40).unwrap();
+    assert_eq!(fixture.earn(1), Err(RewardError::RateLimited.into()));
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, CAPACITY as u64);
+    assert_eq!(reward_account.earn_tokens, 0);
+
+    // Three seconds buy back 30 points
+    fixture.env.set_clock(1_003);
+    assert_eq!(fixture.earn(31), Err(RewardError::RateLimited.into()));
+    fixture.earn(30).unwrap();
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, CAPACITY as u64 + 30);
+    assert_eq!(reward_account.earn_tokens, 0);
+    assert_eq!(reward_account.last_refill_ts, 1_003);
+}
+
+#[test]
+fn refill_stops_at_the_capacity() {
+    let mut fixture = rate_limited_fixture();
+    fixture.earn(CAPACITY).unwrap();
+
+    // A long wait still only refills one burst
+    fixture.env.set_clock(1_000_000);
+    assert_eq!(
+        fixture.earn(CAPACITY + 1),
+        Err(RewardError::RateLimited.into())
+    );
+    fixture.earn(CAPACITY).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 2 * CAPACITY as u64);
+}
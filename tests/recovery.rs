@@ -0,0 +1,215 @@
+mod common;
+
+use common::Fixture;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{RewardAccount, RewardError, RewardInstruction, MAX_GUARDIANS};
+
+struct Recovery {
+    fixture: Fixture,
+    guardians: Vec<Pubkey>,
+    new_owner: Pubkey,
+}
+
+// A user with 100 points protected by three guardians, two of which must sign
+fn recovery_fixture() -> Recovery {
+    let mut fixture = Fixture::new(100);
+    let guardians: Vec<Pubkey> = (0..3).map(|_| fixture.env.create_wallet()).collect();
+    set_guardians(&mut fixture, guardians.clone(), 2).unwrap();
+
+    let new_owner = fixture.env.create_wallet();
+    let mint = fixture.mint;
+    fixture.env.set_reward_account(
+        &new_owner,
+        &RewardAccount {
+            total_points: 5,
+            mint,
+            authority: new_owner,
+            ..RewardAccount::default()
+        },
+    );
+    Recovery {
+        fixture,
+        guardians,
+        new_owner,
+    }
+}
+
+fn set_guardians(
+    fixture: &mut Fixture,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<(), ProgramError> {
+    let guardian_set = fixture.env.guardian_set_address(&fixture.signer);
+    let mut ix = fixture.instruction(RewardInstruction::SetGuardians {
+        guardians,
+        threshold,
+    });
+    ix.accounts.push(AccountMeta::new(guardian_set, false));
+    fixture.env.process(ix)
+}
+
+// Recovery sent by the first of `signers`, with the others co-signing
+fn recover(recovery: &Recovery, signers: &[Pubkey]) -> Instruction {
+    let fixture = &recovery.fixture;
+    let user = fixture.signer;
+    let mut ix = fixture.instruction_signed_by(
+        &signers[0],
+        RewardInstruction::RecoverOwnership {
+            user,
+            new_owner: recovery.new_owner,
+        },
+    );
+    ix.accounts[1].pubkey = fixture.reward_address();
+    ix.accounts.extend([
+        AccountMeta::new_readonly(fixture.env.guardian_set_address(&user), false),
+        AccountMeta::new(fixture.env.reward_address(&recovery.new_owner), false),
+    ]);
+    ix.accounts.extend(
+        signers[1..]
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true)),
+    );
+    ix
+}
+
+#[test]
+fn guardians_at_the_threshold_recover_the_points() {
+    let mut recovery = recovery_fixture();
+
+    let ix = recover(&recovery, &recovery.guardians[..2]);
+    recovery.fixture.env.process(ix).unwrap();
+
+    let new_owner = recovery.new_owner;
+    assert_eq!(recovery.fixture.reward_account().total_points, 0);
+    assert_eq!(
+        recovery.fixture.env.reward_account(&new_owner).total_points,
+        105
+    );
+}
+
+#[test]
+fn pre_funded_guardian_set_address_is_created() {
+    let mut fixture = Fixture::new(100);
+    let guardian_set = fixture.env.guardian_set_address(&fixture.signer);
+    fixture.env.pre_fund(&guardian_set);
+    let guardians = vec![fixture.env.create_wallet()];
+
+    set_guardians(&mut fixture, guardians, 1).unwrap();
+    assert_eq!(
+        fixture.env.account(&guardian_set).unwrap().owner,
+        fixture.env.program_id
+    );
+}
+
+#[test]
+fn recovery_below_the_threshold_is_rejected() {
+    let mut recovery = recovery_fixture();
+    let stranger = recovery.fixture.env.create_wallet();
+
+    // The same guardian twice, or alongside a non-guardian, is still one approval
+    let attempts = [
+        vec![recovery.guardians[0]],
+        vec![recovery.guardians[0], recovery.guardians[0]],
+        vec![recovery.guardians[0], stranger],
+    ];
+    for signers in attempts {
+        let ix = recover(&recovery, &signers);
+        assert_eq!(
+            recovery.fixture.env.process(ix),
+            Err(RewardError::GuardianThresholdNotMet.into())
+        );
+    }
+    assert_eq!(recovery.fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn set_guardians_validates_the_set() {
+    let mut fixture = Fixture::new(0);
+    let guardian = fixture.env.create_wallet();
+    let signer = fixture.signer;
+    let too_many: Vec<Pubkey> = (0..=MAX_GUARDIANS).map(|_| Pubkey::new_unique()).collect();
+
+    let invalid = [
+        (vec![guardian], 0),
+        (vec![guardian], 2),
+        (vec![guardian, guardian], 1),
+        (vec![signer], 1),
+        (too_many, 1),
+    ];
+    for (guardians, threshold) in invalid {
+        assert_eq!(
+            set_guardians(&mut fixture, guardians, threshold),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
+
+#[test]
+fn recovery_into_an_account_of_another_mint_is_rejected() {
+    let mut recovery = recovery_fixture();
+    let new_owner = recovery.new_owner;
+    let mut new_account = recovery.fixture.env.reward_account(&new_owner);
+    new_account.mint = Pubkey::new_unique();
+    recovery
+        .fixture
+        .env
+        .set_reward_account(&new_owner, &new_account);
+
+    let ix = recover(&recovery, &recovery.guardians[..2]);
+    assert_eq!(
+        recovery.fixture.env.process(ix),
+        Err(RewardError::WrongMint.into())
+    );
+    assert_eq!(recovery.fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn recovery_respects_a_paused_account() {
+    let mut recovery = recovery_fixture();
+    let ix = recover(&recovery, &recovery.guardians[..2]);
+
+    // Neither the user's account nor the new owner's may be paused
+    for owner in [recovery.fixture.signer, recovery.new_owner] {
+        let mut reward_account = recovery.fixture.env.reward_account(&owner);
+        reward_account.paused = true;
+        recovery
+            .fixture
+            .env
+            .set_reward_account(&owner, &reward_account);
+
+        assert_eq!(
+            recovery.fixture.env.process(ix.clone()),
+            Err(RewardError::Paused.into())
+        );
+
+        reward_account.paused = false;
+        recovery
+            .fixture
+            .env
+            .set_reward_account(&owner, &reward_account);
+    }
+    assert_eq!(recovery.fixture.reward_account().total_points, 100);
+
+    recovery.fixture.env.process(ix).unwrap();
+    assert_eq!(recovery.fixture.reward_account().total_points, 0);
+}
+
+#[test]
+fn recovery_is_halted_with_the_program() {
+    let mut recovery = recovery_fixture();
+    recovery
+        .fixture
+        .process(RewardInstruction::SetProgramPaused { paused: true })
+        .unwrap();
+
+    let ix = recover(&recovery, &recovery.guardians[..2]);
+    assert_eq!(
+        recovery.fixture.env.process(ix),
+        Err(RewardError::AdminPaused.into())
+    );
+    assert_eq!(recovery.fixture.reward_account().total_points, 100);
+}
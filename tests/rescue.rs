@@ -0,0 +1,119 @@
+mod common;
+
+use common::{default_params, instruction, Fixture, VAULT_BALANCE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::RewardInstruction;
+
+const STRAY_AMOUNT: u64 = 7_500;
+
+// Rescue from `source` into `destination`
+fn rescue(fixture: &Fixture, source: &Pubkey, destination: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(fixture.signer, true),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(fixture.env.config_address(), false),
+        AccountMeta::new_readonly(fixture.env.vault_authority_address(), false),
+    ];
+    instruction(
+        &fixture.env,
+        &RewardInstruction::RescueTokens {
+            amount: STRAY_AMOUNT,
+        },
+        accounts,
+    )
+}
+
+#[test]
+fn admin_rescues_a_stray_token_account() {
+    let mut fixture = Fixture::new(0);
+    let vault_authority = fixture.env.vault_authority_address();
+    let stray_mint = fixture.env.create_mint(&Pubkey::new_unique(), 6);
+    let stray = fixture
+        .env
+        .create_token_account(&stray_mint, &vault_authority, STRAY_AMOUNT);
+    let destination = fixture
+        .env
+        .create_token_account(&stray_mint, &fixture.signer, 0);
+
+    let ix = rescue(&fixture, &stray, &destination);
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.env.token_balance(&stray), 0);
+    assert_eq!(fixture.env.token_balance(&destination), STRAY_AMOUNT);
+}
+
+#[test]
+fn admin_without_a_reward_account_can_rescue() {
+    let mut fixture = Fixture::configured(default_params());
+    let vault_authority = fixture.env.vault_authority_address();
+    let stray_mint = fixture.env.create_mint(&Pubkey::new_unique(), 6);
+    let stray = fixture
+        .env
+        .create_token_account(&stray_mint, &vault_authority, STRAY_AMOUNT);
+    let destination = fixture
+        .env
+        .create_token_account(&stray_mint, &fixture.signer, 0);
+    assert!(fixture.env.account(&fixture.reward_address()).is_none());
+
+    let ix = rescue(&fixture, &stray, &destination);
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(fixture.env.token_balance(&destination), STRAY_AMOUNT);
+}
+
+#[test]
+fn rescue_refuses_reward_mint_tokens() {
+    let mut fixture = Fixture::new(0);
+    let vault_authority = fixture.env.vault_authority_address();
+    let stray = fixture
+        .env
+        .create_token_account(&fixture.mint, &vault_authority, STRAY_AMOUNT);
+
+    let ix = rescue(&fixture, &stray, &fixture.user_token);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(fixture.env.token_balance(&stray), STRAY_AMOUNT);
+}
+
+#[test]
+fn rescue_refuses_the_reward_vault() {
+    let mut fixture = Fixture::new(0);
+
+    let ix = rescue(&fixture, &fixture.vault_token, &fixture.user_token);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+}
+
+#[test]
+fn rescue_requires_admin() {
+    let mut fixture = Fixture::new(0);
+    let vault_authority = fixture.env.vault_authority_address();
+    let stray_mint = fixture.env.create_mint(&Pubkey::new_unique(), 6);
+    let stray = fixture
+        .env
+        .create_token_account(&stray_mint, &vault_authority, STRAY_AMOUNT);
+    let intruder = fixture.env.create_wallet();
+    let destination = fixture.env.create_token_account(&stray_mint, &intruder, 0);
+
+    let mut ix = rescue(&fixture, &stray, &destination);
+    ix.accounts[0] = AccountMeta::new(intruder, true);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert_eq!(fixture.env.token_balance(&stray), STRAY_AMOUNT);
+}
@@ -0,0 +1,207 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{
+    AccountState, ConfigParams, RewardError, RewardInstruction, ScheduledClaim,
+};
+
+const NOW: i64 = 1_000;
+const EXECUTE_AFTER: i64 = 2_000;
+
+// A user with 100 points at NOW
+fn schedule_fixture() -> Fixture {
+    schedule_fixture_with(default_params())
+}
+
+fn schedule_fixture_with(params: ConfigParams) -> Fixture {
+    let fixture = Fixture::with_params(100, params);
+    fixture.env.set_clock(NOW);
+    fixture
+}
+
+fn schedule_instruction(fixture: &Fixture, id: u32, required_points: u32) -> Instruction {
+    let scheduled = fixture.env.scheduled_claim_address(&fixture.signer, id);
+    let mut ix = fixture.instruction(RewardInstruction::ScheduleClaim {
+        required_points,
+        execute_after_ts: EXECUTE_AFTER,
+    });
+    ix.accounts.push(AccountMeta::new(scheduled, false));
+    ix
+}
+
+fn schedule(fixture: &mut Fixture) -> Pubkey {
+    let ix = schedule_instruction(fixture, 0, 40);
+    fixture.env.process(ix).unwrap();
+    fixture.env.scheduled_claim_address(&fixture.signer, 0)
+}
+
+fn execute(fixture: &Fixture, executor: &Pubkey, scheduled: Pubkey) -> Instruction {
+    let mut ix = fixture.instruction_signed_by(executor, RewardInstruction::ExecuteScheduledClaim);
+    ix.accounts.extend([
+        AccountMeta::new(scheduled, false),
+        AccountMeta::new(fixture.signer, false),
+        AccountMeta::new_readonly(fixture.env.vault_authority_address(), false),
+        AccountMeta::new(fixture.reward_address(), false),
+    ]);
+    ix
+}
+
+// Cancel takes the scheduled claim right after the config
+fn cancel(fixture: &Fixture, signer: &Pubkey, scheduled: Pubkey) -> Instruction {
+    let mut ix = fixture.instruction_signed_by(signer, RewardInstruction::CancelScheduledClaim);
+    ix.accounts.push(AccountMeta::new(scheduled, false));
+    ix
+}
+
+#[test]
+fn schedule_debits_points_and_records_the_claim() {
+    let mut fixture = schedule_fixture();
+
+    let scheduled = schedule(&mut fixture);
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 60);
+    assert_eq!(reward_account.next_schedule_id, 1);
+    assert_eq!(reward_account.scheduled_claims, 1);
+
+    let record =
+        ScheduledClaim::try_from_account_data(&fixture.env.account(&scheduled).unwrap().data)
+            .unwrap();
+    assert_eq!(record.user, fixture.signer);
+    assert_eq!(record.destination, fixture.user_token);
+    assert_eq!(record.amount, 40 * EXCHANGE_RATE);
+    assert_eq!(record.execute_after_ts, EXECUTE_AFTER);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn pre_funded_scheduled_claim_address_is_created() {
+    let mut fixture = schedule_fixture();
+    let address = fixture.env.scheduled_claim_address(&fixture.signer, 0);
+    fixture.env.pre_fund(&address);
+
+    let scheduled = schedule(&mut fixture);
+
+    let record =
+        ScheduledClaim::try_from_account_data(&fixture.env.account(&scheduled).unwrap().data)
+            .unwrap();
+    assert_eq!(record.amount, 40 * EXCHANGE_RATE);
+}
+
+#[test]
+fn anyone_executes_a_due_scheduled_claim() {
+    let mut fixture = schedule_fixture();
+    let scheduled = schedule(&mut fixture);
+    let rent = fixture.env.lamports(&scheduled);
+    let user_lamports = fixture.env.lamports(&fixture.signer);
+    let executor = fixture.env.create_wallet();
+
+    let early = execute(&fixture, &executor, scheduled);
+    assert_eq!(
+        fixture.env.process(early),
+        Err(RewardError::ClaimNotYetExecutable.into())
+    );
+
+    fixture.env.set_clock(EXECUTE_AFTER);
+    let due = execute(&fixture, &executor, scheduled);
+    fixture.env.process(due).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        40 * EXCHANGE_RATE
+    );
+    assert!(fixture.env.account(&scheduled).is_none());
+    assert_eq!(fixture.env.lamports(&fixture.signer), user_lamports + rent);
+    assert_eq!(fixture.reward_account().scheduled_claims, 0);
+}
+
+#[test]
+fn cancel_refunds_points_before_execution() {
+    let mut fixture = schedule_fixture();
+    let scheduled = schedule(&mut fixture);
+
+    let signer = fixture.signer;
+    fixture
+        .env
+        .process(cancel(&fixture, &signer, scheduled))
+        .unwrap();
+
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 100);
+    assert_eq!(reward_account.rewards_claimed, 0);
+    assert!(fixture.env.account(&scheduled).is_none());
+
+    // Nothing is left to execute
+    fixture.env.set_clock(EXECUTE_AFTER);
+    let executor = fixture.env.create_wallet();
+    let ix = execute(&fixture, &executor, scheduled);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
+
+#[test]
+fn only_the_user_can_cancel() {
+    let mut fixture = schedule_fixture();
+    let scheduled = schedule(&mut fixture);
+    let intruder = fixture.env.create_wallet();
+
+    let ix = cancel(&fixture, &intruder, scheduled);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(fixture.env.account(&scheduled).is_some());
+}
+
+#[test]
+fn scheduled_claims_are_capped_per_user() {
+    let mut fixture = schedule_fixture_with(ConfigParams {
+        max_scheduled_claims: 2,
+        ..default_params()
+    });
+
+    for id in 0..2 {
+        let ix = schedule_instruction(&fixture, id, 10);
+        fixture.env.process(ix).unwrap();
+    }
+    let ix = schedule_instruction(&fixture, 2, 10);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::TooManyScheduledClaims.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 80);
+
+    // Executing one frees a slot
+    fixture.env.set_clock(EXECUTE_AFTER);
+    let executor = fixture.env.create_wallet();
+    let first = fixture.env.scheduled_claim_address(&fixture.signer, 0);
+    let ix = execute(&fixture, &executor, first);
+    fixture.env.process(ix).unwrap();
+
+    let ix = schedule_instruction(&fixture, 2, 10);
+    fixture.env.process(ix).unwrap();
+    assert_eq!(fixture.reward_account().scheduled_claims, 2);
+}
+
+#[test]
+fn execute_rejects_an_impostor_token_program() {
+    let mut fixture = schedule_fixture();
+    let scheduled = schedule(&mut fixture);
+    fixture.env.set_clock(EXECUTE_AFTER);
+
+    let mut ix = execute(&fixture, &fixture.signer, scheduled);
+    ix.accounts[4].pubkey = Pubkey::new_unique();
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert!(fixture.env.account(&scheduled).is_some());
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{default_params, Fixture, EXCHANGE_RATE, VAULT_BALANCE};
+use wagus_reward_system::{
+    ConfigParams, RewardAccount, RewardInstruction, UserStatus, STATUS_ACCOUNT_PAUSED,
+    STATUS_CLAIMED_THIS_EPOCH, STATUS_PROGRAM_PAUSED,
+};
+
+fn full_status(fixture: &mut Fixture) -> UserStatus {
+    fixture.process(RewardInstruction::GetFullStatus).unwrap();
+    UserStatus::try_from_slice(&fixture.env.return_data().unwrap()).unwrap()
+}
+
+#[test]
+fn full_status_reports_every_field() {
+    let mut fixture = Fixture::configured(ConfigParams {
+        claim_point_fee: 2,
+        earn_cooldown_secs: 60,
+        claim_cooldown_secs: 600,
+        one_claim_per_epoch: true,
+        ..default_params()
+    });
+    fixture.env.set_clock(1_000);
+    fixture.env.set_epoch(5);
+    let mint = fixture.mint;
+    fixture.set_reward_account(&RewardAccount {
+        total_points: 42,
+        rewards_claimed: 3,
+        mint,
+        staked_points: 7,
+        pending_points: 4,
+        lifetime_points: 90,
+        dust_credit: 250,
+        paused: true,
+        last_earn_ts: 990,
+        last_claim_ts: 900,
+        next_claim_epoch: 6,
+        ..RewardAccount::default()
+    });
+
+    assert_eq!(
+        full_status(&mut fixture),
+        UserStatus {
+            total_points: 42,
+            staked_points: 7,
+            pending_points: 4,
+            lifetime_points: 90,
+            rewards_claimed: 3,
+            dust_credit: 250,
+            claimable_amount: 40 * EXCHANGE_RATE,
+            flags: STATUS_ACCOUNT_PAUSED | STATUS_CLAIMED_THIS_EPOCH,
+            earn_cooldown_remaining: 50,
+            claim_cooldown_remaining: 500,
+        }
+    );
+}
+
+#[test]
+fn full_status_bounds_claimable_amount_by_the_vault_cap() {
+    let mut fixture = Fixture::with_params(
+        100,
+        ConfigParams {
+            max_vault_fraction_bps: 100,
+            ..default_params()
+        },
+    );
+
+    assert_eq!(
+        full_status(&mut fixture).claimable_amount,
+        VAULT_BALANCE / 100
+    );
+}
+
+#[test]
+fn full_status_flags_a_paused_program_and_elapsed_cooldowns() {
+    let mut fixture = Fixture::with_params(
+        10,
+        ConfigParams {
+            earn_cooldown_secs: 60,
+            claim_cooldown_secs: 600,
+            ..default_params()
+        },
+    );
+    fixture.env.set_clock(10_000);
+    fixture.set_reward_account(&RewardAccount {
+        last_earn_ts: 1_000,
+        last_claim_ts: 1_000,
+        ..fixture.reward_account()
+    });
+    fixture
+        .process(RewardInstruction::SetProgramPaused { paused: true })
+        .unwrap();
+
+    let status = full_status(&mut fixture);
+    assert_eq!(status.flags, STATUS_PROGRAM_PAUSED);
+    assert_eq!(status.earn_cooldown_remaining, 0);
+    assert_eq!(status.claim_cooldown_remaining, 0);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use common::{default_params, Fixture, EXCHANGE_RATE, VAULT_BALANCE};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{
+    find_stream_address, Config, ConfigParams, RewardError, RewardInstruction,
+};
+
+const NOW: i64 = 1_000;
+const DURATION: u32 = 100;
+const TOTAL_AMOUNT: u64 = 40 * EXCHANGE_RATE;
+
+// A user with 100 points at NOW
+fn stream_fixture(params: ConfigParams) -> Fixture {
+    let fixture = Fixture::with_params(100, params);
+    fixture.env.set_clock(NOW);
+    fixture
+}
+
+fn stream_address(fixture: &Fixture, id: u32) -> Pubkey {
+    find_stream_address(&fixture.env.program_id, &fixture.signer, id).0
+}
+
+// Stream 40 points' worth of tokens over DURATION seconds
+fn open_stream(fixture: &mut Fixture) -> Pubkey {
+    let stream = stream_address(fixture, 0);
+    let mut ix = fixture.instruction(RewardInstruction::OpenStream {
+        required_points: 40,
+        total_amount: TOTAL_AMOUNT,
+        duration: DURATION,
+    });
+    ix.accounts.push(AccountMeta::new(stream, false));
+    fixture.env.process(ix).unwrap();
+    stream
+}
+
+fn withdraw(fixture: &mut Fixture, stream: Pubkey) -> Result<(), ProgramError> {
+    let mut ix = fixture.instruction(RewardInstruction::WithdrawStream);
+    ix.accounts.extend([
+        AccountMeta::new(stream, false),
+        AccountMeta::new_readonly(fixture.env.vault_authority_address(), false),
+    ]);
+    fixture.env.process(ix)
+}
+
+fn cancel(fixture: &Fixture, signer: &Pubkey, stream: Pubkey) -> Instruction {
+    let mut ix = fixture.instruction_signed_by(signer, RewardInstruction::CancelStream);
+    ix.accounts.extend([
+        AccountMeta::new(stream, false),
+        AccountMeta::new(fixture.signer, false),
+        AccountMeta::new_readonly(fixture.env.vault_authority_address(), false),
+        AccountMeta::new(fixture.reward_address(), false),
+    ]);
+    ix
+}
+
+#[test]
+fn withdrawals_pay_out_what_has_accrued() {
+    let mut fixture = stream_fixture(default_params());
+    let stream = open_stream(&mut fixture);
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 60);
+    assert_eq!(reward_account.scheduled_claims, 1);
+
+    // A quarter of the way in, then at three quarters
+    fixture.env.set_clock(NOW + 25);
+    withdraw(&mut fixture, stream).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        TOTAL_AMOUNT / 4
+    );
+    fixture.env.set_clock(NOW + 75);
+    withdraw(&mut fixture, stream).unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        3 * TOTAL_AMOUNT / 4
+    );
+
+    // Past the end only the remainder is paid and the stream closes
+    fixture.env.set_clock(NOW + 1_000);
+    withdraw(&mut fixture, stream).unwrap();
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), TOTAL_AMOUNT);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE - TOTAL_AMOUNT
+    );
+    assert!(fixture.env.account(&stream).is_none());
+    assert_eq!(fixture.reward_account().scheduled_claims, 0);
+}
+
+#[test]
+fn pre_funded_stream_address_is_created() {
+    let mut fixture = stream_fixture(default_params());
+    let address = stream_address(&fixture, 0);
+    fixture.env.pre_fund(&address);
+
+    let stream = open_stream(&mut fixture);
+
+    fixture.env.set_clock(NOW + DURATION as i64);
+    withdraw(&mut fixture, stream).unwrap();
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), TOTAL_AMOUNT);
+}
+
+#[test]
+fn cancel_settles_accrued_tokens_and_refunds_the_rest() {
+    let mut fixture = stream_fixture(default_params());
+    let stream = open_stream(&mut fixture);
+
+    fixture.env.set_clock(NOW + 25);
+    let ix = cancel(&fixture, &fixture.signer, stream);
+    fixture.env.process(ix).unwrap();
+
+    assert_eq!(
+        fixture.env.token_balance(&fixture.user_token),
+        TOTAL_AMOUNT / 4
+    );
+    let reward_account = fixture.reward_account();
+    assert_eq!(reward_account.total_points, 90);
+    assert_eq!(reward_account.scheduled_claims, 0);
+    assert!(fixture.env.account(&stream).is_none());
+}
+
+// Hand the config to a separate admin so the stream owner is not also the admin
+fn set_admin(fixture: &mut Fixture, admin: Pubkey) {
+    let config_address = fixture.env.config_address();
+    let mut account = fixture.env.account(&config_address).unwrap().clone();
+    let config = Config {
+        admin,
+        ..fixture.env.config()
+    };
+    account.data = common::account_data(&config);
+    fixture.env.set_account(config_address, account);
+}
+
+#[test]
+fn config_chooses_who_cancels_streams() {
+    for admin_cancels_streams in [false, true] {
+        let mut fixture = stream_fixture(ConfigParams {
+            admin_cancels_streams,
+            ..default_params()
+        });
+        let admin = fixture.env.create_wallet();
+        set_admin(&mut fixture, admin);
+        let stream = open_stream(&mut fixture);
+
+        let (allowed, refused) = match admin_cancels_streams {
+            true => (admin, fixture.signer),
+            false => (fixture.signer, admin),
+        };
+        let ix = cancel(&fixture, &refused, stream);
+        assert_eq!(
+            fixture.env.process(ix),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        let ix = cancel(&fixture, &allowed, stream);
+        fixture.env.process(ix).unwrap();
+        assert_eq!(fixture.reward_account().total_points, 100);
+    }
+}
+
+#[test]
+fn stream_worth_more_than_the_points_is_rejected() {
+    let mut fixture = stream_fixture(default_params());
+
+    let mut ix = fixture.instruction(RewardInstruction::OpenStream {
+        required_points: 40,
+        total_amount: TOTAL_AMOUNT + 1,
+        duration: DURATION,
+    });
+    ix.accounts
+        .push(AccountMeta::new(stream_address(&fixture, 0), false));
+    assert_eq!(fixture.env.process(ix), Err(ProgramError::InvalidArgument));
+    assert_eq!(fixture.reward_account().total_points, 100);
+}
+
+#[test]
+fn stream_payouts_reject_a_vault_of_another_mint() {
+    let mut fixture = stream_fixture(default_params());
+    let stream = open_stream(&mut fixture);
+    fixture.env.set_clock(NOW + 25);
+
+    // Held by the vault authority, but not of the reward mint
+    let other_mint = fixture.env.create_mint(&fixture.signer, 6);
+    let vault_authority = fixture.env.vault_authority_address();
+    fixture.vault_token =
+        fixture
+            .env
+            .create_token_account(&other_mint, &vault_authority, VAULT_BALANCE);
+
+    assert_eq!(
+        withdraw(&mut fixture, stream),
+        Err(RewardError::WrongMint.into())
+    );
+    let ix = cancel(&fixture, &fixture.signer, stream);
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert_eq!(
+        fixture.env.token_balance(&fixture.vault_token),
+        VAULT_BALANCE
+    );
+    assert!(fixture.env.account(&stream).is_some());
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::Fixture;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account as TokenAccount;
+use wagus_reward_system::{RewardError, RewardInstruction};
+
+// InitVault signed by `signer`, creating the token account at `vault`
+fn init_vault(fixture: &Fixture, signer: &Pubkey, vault: &Pubkey) -> Instruction {
+    let mut ix = fixture.instruction_signed_by(signer, RewardInstruction::InitVault);
+    ix.accounts[1] = AccountMeta::new(*vault, false);
+    ix.accounts.push(AccountMeta::new_readonly(
+        fixture.env.vault_authority_address(),
+        false,
+    ));
+    ix
+}
+
+// A fixture whose mint has no vault yet
+fn vaultless_fixture() -> Fixture {
+    let mut fixture = Fixture::new(0);
+    let vault = fixture.env.vault_address(&fixture.mint);
+    fixture.env.remove_account(&vault);
+    fixture
+}
+
+#[test]
+fn init_vault_creates_a_token_account_held_by_the_vault_authority() {
+    let mut fixture = vaultless_fixture();
+    let vault = fixture.env.vault_address(&fixture.mint);
+
+    let ix = init_vault(&fixture, &fixture.signer, &vault);
+    fixture.env.process(ix).unwrap();
+
+    let account = fixture.env.account(&vault).unwrap();
+    assert_eq!(account.owner, spl_token::id());
+    let token_account = TokenAccount::unpack(&account.data).unwrap();
+    assert_eq!(token_account.mint, fixture.mint);
+    assert_eq!(token_account.owner, fixture.env.vault_authority_address());
+    assert_eq!(token_account.amount, 0);
+
+    // A second InitVault for the same mint is rejected
+    let ix = init_vault(&fixture, &fixture.signer, &vault);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::AlreadyInitialized.into())
+    );
+}
+
+#[test]
+fn init_vault_requires_admin() {
+    let mut fixture = vaultless_fixture();
+    let intruder = fixture.env.create_wallet();
+    let vault = fixture.env.vault_address(&fixture.mint);
+
+    let ix = init_vault(&fixture, &intruder, &vault);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(ProgramError::IncorrectAuthority)
+    );
+    assert!(fixture.env.account(&vault).is_none());
+}
+
+#[test]
+fn init_vault_rejects_an_address_other_than_the_vault_pda() {
+    let mut fixture = Fixture::new(0);
+    let elsewhere = Pubkey::new_unique();
+
+    let ix = init_vault(&fixture, &fixture.signer, &elsewhere);
+    assert_eq!(
+        fixture.env.process(ix),
+        Err(RewardError::InvalidVault.into())
+    );
+}
+
+#[test]
+fn init_vault_only_accepts_the_reward_mint() {
+    let mut fixture = Fixture::new(0);
+    let foreign_mint = fixture.env.create_mint(&fixture.signer, 6);
+    let vault = fixture.env.vault_address(&foreign_mint);
+
+    let mut ix = init_vault(&fixture, &fixture.signer, &vault);
+    ix.accounts[2].pubkey = foreign_mint;
+    assert_eq!(fixture.env.process(ix), Err(RewardError::WrongMint.into()));
+    assert!(fixture.env.account(&vault).is_none());
+}
+
+#[test]
+fn claim_from_a_vault_not_held_by_the_vault_authority_fails() {
+    let mut fixture = Fixture::new(100);
+    fixture.vault_token =
+        fixture
+            .env
+            .create_token_account(&fixture.mint, &fixture.signer, 1_000_000);
+
+    assert_eq!(
+        fixture.process(RewardInstruction::Claim {
+            required_points: 40,
+            expected_total_points: None,
+        }),
+        Err(RewardError::InvalidVault.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 100);
+    assert_eq!(fixture.env.token_balance(&fixture.user_token), 0);
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use common::{default_params, ed25519_instruction, Fixture};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use wagus_reward_system::{
+    earn_voucher_message, AccountState, ConfigParams, NonceRegistry, RewardError, RewardInstruction,
+};
+
+const POINTS: u32 = 25;
+const NONCE: u64 = 7;
+const EXPIRY_TS: i64 = 1_000;
+const SIGNATURE: [u8; 64] = [3; 64];
+
+struct Voucher {
+    fixture: Fixture,
+    backend: Pubkey,
+}
+
+fn voucher_fixture() -> Voucher {
+    let backend = Pubkey::new_unique();
+    let fixture = Fixture::with_params(
+        0,
+        ConfigParams {
+            earn_authority: backend,
+            ..default_params()
+        },
+    );
+    Voucher { fixture, backend }
+}
+
+// The backend's signature check followed by the earn it authorizes
+fn redeem(fixture: &Fixture, signer: &Pubkey, points: u32, nonce: u64) -> [Instruction; 2] {
+    let message = earn_voucher_message(&fixture.signer, points, nonce, EXPIRY_TS);
+    let mut earn = fixture.instruction(RewardInstruction::EarnWithVoucher {
+        points,
+        nonce,
+        expiry_ts: EXPIRY_TS,
+        signature: SIGNATURE,
+    });
+    earn.accounts.extend([
+        AccountMeta::new_readonly(solana_instructions_sysvar::id(), false),
+        AccountMeta::new(fixture.env.nonce_registry_address(&fixture.signer), false),
+    ]);
+    [ed25519_instruction(signer, &SIGNATURE, &message), earn]
+}
+
+#[test]
+fn voucher_is_credited_once() {
+    let Voucher {
+        mut fixture,
+        backend,
+    } = voucher_fixture();
+    fixture.env.set_clock(EXPIRY_TS);
+
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE);
+    fixture.env.process_transaction(&instructions).unwrap();
+    assert_eq!(fixture.reward_account().total_points, POINTS as u64);
+
+    // Replaying the same voucher is rejected
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::VoucherAlreadyUsed.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, POINTS as u64);
+
+    // A fresh nonce is another voucher
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE + 1);
+    fixture.env.process_transaction(&instructions).unwrap();
+    assert_eq!(fixture.reward_account().total_points, 2 * POINTS as u64);
+}
+
+fn nonce_registry(fixture: &Fixture) -> NonceRegistry {
+    let address = fixture.env.nonce_registry_address(&fixture.signer);
+    NonceRegistry::try_from_account_data(&fixture.env.account(&address).unwrap().data).unwrap()
+}
+
+#[test]
+fn pre_funded_nonce_registry_address_is_created() {
+    let Voucher {
+        mut fixture,
+        backend,
+    } = voucher_fixture();
+    fixture.env.set_clock(EXPIRY_TS);
+    let address = fixture.env.nonce_registry_address(&fixture.signer);
+    fixture.env.pre_fund(&address);
+
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE);
+    fixture.env.process_transaction(&instructions).unwrap();
+
+    assert_eq!(fixture.reward_account().total_points, POINTS as u64);
+    assert_eq!(nonce_registry(&fixture).next_nonce, NONCE + 1);
+}
+
+#[test]
+fn redeeming_a_nonce_retires_the_lower_ones() {
+    let Voucher {
+        mut fixture,
+        backend,
+    } = voucher_fixture();
+
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE);
+    fixture.env.process_transaction(&instructions).unwrap();
+    let registry = nonce_registry(&fixture);
+    assert_eq!(registry.user, fixture.signer);
+    assert_eq!(registry.next_nonce, NONCE + 1);
+
+    // An older voucher that was never redeemed can no longer be
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE - 1);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::VoucherAlreadyUsed.into())
+    );
+
+    // Nonces may skip ahead
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE + 10);
+    fixture.env.process_transaction(&instructions).unwrap();
+    assert_eq!(nonce_registry(&fixture).next_nonce, NONCE + 11);
+    assert_eq!(fixture.reward_account().total_points, 2 * POINTS as u64);
+}
+
+#[test]
+fn expired_voucher_is_rejected() {
+    let Voucher {
+        mut fixture,
+        backend,
+    } = voucher_fixture();
+    fixture.env.set_clock(EXPIRY_TS + 1);
+
+    let instructions = redeem(&fixture, &backend, POINTS, NONCE);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::VoucherExpired.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 0);
+}
+
+#[test]
+fn voucher_signed_by_someone_else_is_rejected() {
+    let Voucher {
+        mut fixture,
+        backend,
+    } = voucher_fixture();
+    let stranger = Pubkey::new_unique();
+
+    // Signed by a stranger, or by the backend over different points
+    let forged = redeem(&fixture, &stranger, POINTS, NONCE);
+    let mut altered = redeem(&fixture, &backend, POINTS, NONCE);
+    altered[1] = redeem(&fixture, &backend, 2 * POINTS, NONCE)[1].clone();
+    for instructions in [forged, altered] {
+        assert_eq!(
+            fixture.env.process_transaction(&instructions),
+            Err(RewardError::InvalidVoucher.into())
+        );
+    }
+
+    // Without the signature check in front of it
+    let [_, earn] = redeem(&fixture, &backend, POINTS, NONCE);
+    assert_eq!(
+        fixture.env.process(earn),
+        Err(RewardError::InvalidVoucher.into())
+    );
+    assert_eq!(fixture.reward_account().total_points, 0);
+}
+
+#[test]
+fn vouchers_signed_by_the_user_are_rejected() {
+    let mut fixture = Fixture::new(0);
+    let signer = fixture.signer;
+
+    let instructions = redeem(&fixture, &signer, POINTS, NONCE);
+    assert_eq!(
+        fixture.env.process_transaction(&instructions),
+        Err(RewardError::InvalidVoucher.into())
+    );
+}